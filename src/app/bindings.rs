@@ -0,0 +1,335 @@
+use super::state::State;
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::{collections::BTreeMap, fmt};
+
+/// The actions that change the state of the [Exercise].
+///
+/// [Exercise]: crate::app::exercise::Exercise
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Stop,
+    Restart,
+    Pause,
+    Settings,
+}
+
+impl Action {
+    /// All the actions that can be bound to a key.
+    pub const ALL: [Action; 5] = [
+        Action::Quit,
+        Action::Stop,
+        Action::Restart,
+        Action::Pause,
+        Action::Settings,
+    ];
+
+    /// Returns the name of the action in the configuration file.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Stop => "stop",
+            Action::Restart => "restart",
+            Action::Pause => "pause",
+            Action::Settings => "settings",
+        }
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Quit => write!(f, "Quit"),
+            Action::Stop => write!(f, "Stop"),
+            Action::Restart => write!(f, "Restart"),
+            Action::Pause => write!(f, "Pause"),
+            Action::Settings => write!(f, "Settings"),
+        }
+    }
+}
+
+/// A combination of modifiers and a key, like "ctrl+r".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// A key that is pressed together with the control key.
+    pub const fn ctrl(c: char) -> KeyBinding {
+        KeyBinding {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Parse a key binding from the configuration file.
+    ///
+    /// The modifiers "ctrl", "alt" and "shift" are followed by a single character,
+    /// or a named key like "esc", "home", "end", "pageup", "pagedown", "insert", "delete" or "f1" to "f12".
+    /// The keys that are used to type need a ctrl or alt modifier.
+    pub fn parse(binding: &str) -> Result<KeyBinding> {
+        let invalid = || anyhow!("Invalid key binding '{}'", binding);
+
+        let lowercase = binding.to_lowercase();
+        let mut parts: Vec<&str> = lowercase.split('+').collect();
+        // The plus key itself leaves an empty part at the end
+        let key = match parts.pop() {
+            Some("") if parts.last() == Some(&"") => {
+                parts.pop();
+                "+"
+            }
+            Some(key) => key,
+            None => return Err(invalid()),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers |= match part {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(invalid()),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backspace" => KeyCode::Backspace,
+                "space" => KeyCode::Char(' '),
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "insert" => KeyCode::Insert,
+                "delete" => KeyCode::Delete,
+                _ => match key.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n) if (1..=12).contains(&n) => KeyCode::F(n),
+                    _ => return Err(invalid()),
+                },
+            },
+        };
+
+        let typing = matches!(
+            code,
+            KeyCode::Char(_) | KeyCode::Enter | KeyCode::Tab | KeyCode::Backspace
+        );
+        if typing && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return Err(anyhow!(
+                "The key binding '{}' needs ctrl or alt, the key is used to type",
+                binding
+            ));
+        }
+
+        Ok(KeyBinding { code, modifiers })
+    }
+
+    /// Returns true if the key event is this key binding, the case of a character is ignored.
+    pub fn matches(&self, key_event: &KeyEvent) -> bool {
+        let modifiers = key_event.modifiers - KeyModifiers::SHIFT;
+        match (self.code, key_event.code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                a.eq_ignore_ascii_case(&b) && modifiers == self.modifiers - KeyModifiers::SHIFT
+            }
+            (a, b) => a == b && key_event.modifiers == self.modifiers,
+        }
+    }
+
+    /// Returns true if a key event can match both key bindings.
+    pub fn overlaps(&self, other: &KeyBinding) -> bool {
+        match (self.code, other.code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => {
+                a.eq_ignore_ascii_case(&b)
+                    && self.modifiers - KeyModifiers::SHIFT == other.modifiers - KeyModifiers::SHIFT
+            }
+            (a, b) => a == b && self.modifiers == other.modifiers,
+        }
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{}", name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// The keys that are bound to the actions.
+///
+/// The quit and the stop action have the same default key,
+/// because they are never available in the same state.
+#[derive(Clone, Debug)]
+pub struct KeyBindings {
+    quit: KeyBinding,
+    stop: KeyBinding,
+    restart: KeyBinding,
+    pause: KeyBinding,
+    settings: KeyBinding,
+}
+
+impl KeyBindings {
+    /// Create the key bindings from the configuration file, the actions that are omitted keep their default key.
+    pub fn from_config(config: &BTreeMap<String, String>) -> Result<KeyBindings> {
+        let mut bindings = KeyBindings::default();
+        for (name, binding) in config {
+            let action = Action::ALL
+                .into_iter()
+                .find(|action| action.name() == name)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Unknown action '{}', the available actions are: {}",
+                        name,
+                        Action::ALL.map(|action| action.name()).join(", ")
+                    )
+                })?;
+            *bindings.get_mut(action) = KeyBinding::parse(binding)?;
+        }
+
+        // Only the first of two actions with the same key in a state could ever be used
+        for state in State::ALL {
+            let actions = state.actions();
+            for (index, &first) in actions.iter().enumerate() {
+                let key = bindings.get(first);
+                if let Some(second) = actions[index + 1..]
+                    .iter()
+                    .find(|&&second| key.overlaps(&bindings.get(second)))
+                {
+                    return Err(anyhow!(
+                        "The actions '{}' and '{}' are both bound to {}, they are used in the same state",
+                        first.name(),
+                        second.name(),
+                        key
+                    ));
+                }
+            }
+        }
+        Ok(bindings)
+    }
+
+    /// Returns the key that is bound to the action.
+    pub fn get(&self, action: Action) -> KeyBinding {
+        match action {
+            Action::Quit => self.quit,
+            Action::Stop => self.stop,
+            Action::Restart => self.restart,
+            Action::Pause => self.pause,
+            Action::Settings => self.settings,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut KeyBinding {
+        match action {
+            Action::Quit => &mut self.quit,
+            Action::Stop => &mut self.stop,
+            Action::Restart => &mut self.restart,
+            Action::Pause => &mut self.pause,
+            Action::Settings => &mut self.settings,
+        }
+    }
+
+    /// Returns the first of the given actions that is bound to the key event.
+    pub fn action(&self, key_event: &KeyEvent, actions: &[Action]) -> Option<Action> {
+        actions
+            .iter()
+            .copied()
+            .find(|&action| self.get(action).matches(key_event))
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            quit: KeyBinding::ctrl('c'),
+            stop: KeyBinding::ctrl('c'),
+            restart: KeyBinding::ctrl('r'),
+            pause: KeyBinding::ctrl('p'),
+            settings: KeyBinding::ctrl('s'),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(bindings: &[(&str, &str)]) -> BTreeMap<String, String> {
+        bindings
+            .iter()
+            .map(|&(action, key)| (action.to_string(), key.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_key_bindings() {
+        assert_eq!(KeyBinding::parse("ctrl+r").unwrap(), KeyBinding::ctrl('r'));
+        assert_eq!(KeyBinding::parse("Ctrl+R").unwrap(), KeyBinding::ctrl('r'));
+        assert_eq!(KeyBinding::parse("ctrl++").unwrap(), KeyBinding::ctrl('+'));
+        assert_eq!(
+            KeyBinding::parse("f5").unwrap(),
+            KeyBinding {
+                code: KeyCode::F(5),
+                modifiers: KeyModifiers::NONE
+            }
+        );
+        assert!(KeyBinding::parse("r").is_err());
+        assert!(KeyBinding::parse("hyper+r").is_err());
+        assert!(KeyBinding::parse("f13").is_err());
+    }
+
+    #[test]
+    fn default_bindings_are_accepted() {
+        assert!(KeyBindings::from_config(&BTreeMap::new()).is_ok());
+    }
+
+    #[test]
+    fn quit_and_stop_may_share_a_key() {
+        let bindings = KeyBindings::from_config(&config(&[("quit", "esc"), ("stop", "esc")]));
+        assert!(bindings.is_ok());
+    }
+
+    #[test]
+    fn actions_of_the_same_state_may_not_share_a_key() {
+        let error = KeyBindings::from_config(&config(&[("pause", "ctrl+r")]))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("'restart'") && error.contains("'pause'"),
+            "{}",
+            error
+        );
+
+        let error = KeyBindings::from_config(&config(&[("settings", "ctrl+shift+c")]))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.contains("'quit'") && error.contains("'settings'"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn unknown_actions_are_rejected() {
+        assert!(KeyBindings::from_config(&config(&[("jump", "ctrl+j")])).is_err());
+    }
+}
@@ -0,0 +1,85 @@
+/// An exercise that is built into the application, so it can be started without a file.
+pub struct Builtin {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub text: &'static str,
+}
+
+/// All the exercises that are built in, from the home row to complete sentences.
+pub const BUILTINS: [Builtin; 7] = [
+    Builtin {
+        name: "home-row",
+        description: "Words with only the letters of the home row",
+        text: "\
+asdf jkl; asdf jkl; fjdk slal fjdk slal
+a sad lad; a glad dad; all fall; ask dad
+flask glass salad falls; a lass has a flask
+dash flag gala hall; jag lash sash gash
+",
+    },
+    Builtin {
+        name: "top-row",
+        description: "Words with the letters of the top row",
+        text: "\
+qwer tyui op qwer tyui op
+we were there; your typewriter; pour it out
+quiet tower; pretty witty poet; out of our route
+ripe fruit; write it top to bottom; trip report
+",
+    },
+    Builtin {
+        name: "bottom-row",
+        description: "Words with the letters of the bottom row",
+        text: "\
+zxcv bnm, zxcv bnm, ./ zxcv bnm,
+buzz box; black cab; zinc comb; my van
+maze of brick; a bank by the zoo; come back
+mix and match; a calm cozy cabin; vivid zebra
+",
+    },
+    Builtin {
+        name: "numbers",
+        description: "The number row and the symbols above it",
+        text: "\
+1234 5678 90 1234 5678 90
+12 apples, 34 pears, 56 plums and 78 figs
+call 555-0199 before 10:30 or after 16:45
+100% of $250 is 2 * 125 = (5 ^ 3) * 2
+#1 in 2024, #3 in 1999 & #7 in 1987!
+",
+    },
+    Builtin {
+        name: "pangrams",
+        description: "Sentences that use every letter of the alphabet",
+        text: "\
+The quick brown fox jumps over the lazy dog.
+Pack my box with five dozen liquor jugs.
+How vexingly quick daft zebras jump!
+Sphinx of black quartz, judge my vow.
+The five boxing wizards jump quickly.
+Jackdaws love my big sphinx of quartz.
+",
+    },
+    Builtin {
+        name: "common-words",
+        description: "The most common words of the English language",
+        text: "\
+the of and to in is you that it he was for on are as with his they
+at be this have from or one had by word but not what all were we when
+your can said there use an each which she do how their if will up other
+about out many then them these so some her would make like him into time
+has look two more write go see number no way could people my than first
+",
+    },
+    Builtin {
+        name: "punctuation",
+        description: "Sentences with quotes, brackets and punctuation",
+        text: "\
+\"Wait,\" she said, \"is it (really) over?\"
+Yes; no: maybe... it's hard to tell, isn't it?
+The list [a, b, c] and the set {x, y, z} differ.
+Use <tags> for markup, and 'quotes' for strings!
+Files: ~/notes.txt, ./src/main.rs and ../README.md
+",
+    },
+];
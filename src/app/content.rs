@@ -1,12 +1,64 @@
+use super::prompt::graphemes;
 use rand::{seq::SliceRandom, thread_rng};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
-use std::path::PathBuf;
+use std::{fmt, path::PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The way the text of the file is split into prompts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Split {
+    Line,
+    Word,
+    Sentence,
+    Paragraph,
+    Chars(usize), // the reflowed text in prompts of at most the given amount of characters
+}
+
+impl Split {
+    /// The values that are offered in the settings form of the menu.
+    pub const PRESETS: [&'static str; 6] = [
+        "line",
+        "word",
+        "sentence",
+        "paragraph",
+        "chars:40",
+        "chars:80",
+    ];
+
+    /// Returns the split with the given name, the amount of characters is given as "chars:N".
+    pub fn from_name(name: &str) -> Option<Split> {
+        match name {
+            "line" => Some(Split::Line),
+            "word" => Some(Split::Word),
+            "sentence" => Some(Split::Sentence),
+            "paragraph" => Some(Split::Paragraph),
+            _ => name
+                .strip_prefix("chars:")
+                .and_then(|n| n.parse().ok())
+                .filter(|&n| n > 0)
+                .map(Split::Chars),
+        }
+    }
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Split::Line => write!(f, "line"),
+            Split::Word => write!(f, "word"),
+            Split::Sentence => write!(f, "sentence"),
+            Split::Paragraph => write!(f, "paragraph"),
+            Split::Chars(n) => write!(f, "chars:{}", n),
+        }
+    }
+}
 
 /// Contains the collection of prompts that were read from the specified file, together with the relevant settings.
 pub struct Content {
     pub file_path: PathBuf,
-    pub words: bool,
+    pub split: Split,
     pub random: bool,
+    pub endless: bool, // start again at the first prompt after the last prompt
 
     content: Vec<String>,
     prompt_index: usize,
@@ -14,13 +66,20 @@ pub struct Content {
 
 impl Content {
     /// Create the content struct
-    pub fn build(file_path: PathBuf, content: Vec<String>, random: bool, words: bool) -> Content {
+    pub fn build(
+        file_path: PathBuf,
+        content: Vec<String>,
+        random: bool,
+        split: Split,
+        endless: bool,
+    ) -> Content {
         let mut content = Content {
             file_path,
             content,
             prompt_index: 0,
-            words,
+            split,
             random,
+            endless,
         };
 
         if content.random {
@@ -44,31 +103,32 @@ impl Content {
     }
 
     /// Advance the pointer to the next prompt
+    ///
+    /// An endless content starts again after the last prompt, the prompts are shuffled again if the random-flag is set.
     pub fn next_prompt(&mut self) {
         self.prompt_index += 1;
+        if self.endless && self.prompt_index >= self.content.len() {
+            self.reset();
+        }
     }
 
-    /// Returns the current prompt as a vector of characters.
+    /// Returns the current prompt as a vector of grapheme clusters.
     ///
     /// This format makes it easier for the [Prompt] to compare the individual typed characters with the corresponding characters from the prompt.
     ///
     /// [Prompt]: crate::app::prompt::Prompt
-    pub fn get_prompt(&self) -> Option<Vec<char>> {
-        if let Some(s) = self.content.get(self.prompt_index) {
-            Some(s.chars().collect())
-        } else {
-            Option::None
-        }
+    pub fn get_prompt(&self) -> Option<Vec<String>> {
+        self.content.get(self.prompt_index).map(|s| graphemes(s))
     }
 
-    /// Returns all the following prompts in the form of a vector of Strings
+    /// Returns at most `limit` of the following prompts in the form of a slice of Strings
     ///
     /// This format is chosen because the characters in these Strings do not need to be individually styled or compared.
-    pub fn get_next_prompts(&self) -> Vec<String> {
-        match self.content.len() >= self.prompt_index + 1 {
-            true => self.content[self.prompt_index + 1..].to_vec(),
-            false => Vec::new(),
-        }
+    /// The prompts are borrowed, so only the visible part of a long file has to be processed on every draw.
+    pub fn get_next_prompts(&self, limit: usize) -> &[String] {
+        let start = usize::min(self.prompt_index + 1, self.content.len());
+        let end = usize::min(start.saturating_add(limit), self.content.len());
+        &self.content[start..end]
     }
 
     /// Returns the ratio of the finished prompts compared to the total amount of prompts.
@@ -78,21 +138,86 @@ impl Content {
 
     /// Used to split the original text from the specified file into prompts.
     ///
-    /// The text is split after every line, or every word, if the flag is set to true.
-    pub fn into_prompts(content: String, words: bool) -> Vec<String> {
-        match words {
-            false => content
+    /// The text is split after every line or every word as it is in the file.
+    /// For sentences and paragraphs, the hard-wrapped lines of a paragraph are joined first,
+    /// and the prompts that are longer than `limit` characters are broken at the words.
+    pub fn into_prompts(content: String, split: Split, limit: usize) -> Vec<String> {
+        match split {
+            Split::Line => content
                 .lines()
                 .map(|s| s.trim().to_string())
                 .filter(|s| !s.is_empty())
                 .collect(),
-            true => content
+            Split::Word => content
                 .split_whitespace()
                 .map(|s| s.to_string())
                 .filter(|s| !s.is_empty())
                 .collect(),
+            Split::Sentence => Content::paragraphs(&content)
+                .iter()
+                .flat_map(|paragraph| {
+                    paragraph
+                        .unicode_sentences()
+                        .flat_map(|sentence| Content::wrap(sentence, limit))
+                        .collect::<Vec<String>>()
+                })
+                .collect(),
+            Split::Paragraph => Content::paragraphs(&content)
+                .iter()
+                .flat_map(|paragraph| Content::wrap(paragraph, limit))
+                .collect(),
+            Split::Chars(n) => Content::wrap(&content, n),
         }
     }
+
+    /// Returns the paragraphs of the text, which are separated by empty lines, with their lines joined by a space.
+    fn paragraphs(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .collect::<Vec<&str>>()
+            .split(|line| line.trim().is_empty())
+            .map(|lines| {
+                lines
+                    .join(" ")
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            })
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect()
+    }
+
+    /// Break the text at the words into lines of at most `limit` graphemes,
+    /// a word that is longer than the limit is broken at the limit.
+    ///
+    /// The length is counted in graphemes like the prompt does, so a grapheme is never broken.
+    fn wrap(text: &str, limit: usize) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        let mut length = 0;
+        for word in text.split_whitespace() {
+            let mut parts: Vec<&str> = word.graphemes(true).collect();
+            if length > 0 && length + 1 + parts.len() > limit {
+                lines.push(std::mem::take(&mut line));
+                length = 0;
+            }
+            while parts.len() > limit {
+                let rest = parts.split_off(limit);
+                lines.push(parts.concat());
+                parts = rest;
+            }
+            if length > 0 {
+                line.push(' ');
+                length += 1;
+            }
+            line.push_str(&parts.concat());
+            length += parts.len();
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        lines
+    }
 }
 
 /// Serialize the content, he complete list of prompts is not relevant to save
@@ -101,10 +226,41 @@ impl Serialize for Content {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Content", 3)?;
+        let mut state = serializer.serialize_struct("Content", 6)?;
         state.serialize_field("file_path", &self.file_path.file_name().unwrap().to_str())?;
-        state.serialize_field("words", &self.words)?;
+        state.serialize_field("path", &self.file_path.to_str())?;
+        state.serialize_field("words", &(self.split == Split::Word))?;
+        state.serialize_field("split", &self.split.to_string())?;
         state.serialize_field("random", &self.random)?;
+        state.serialize_field("endless", &self.endless)?;
         state.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_at_the_words() {
+        assert_eq!(
+            Content::wrap("the quick brown fox", 10),
+            ["the quick", "brown fox"]
+        );
+        assert_eq!(Content::wrap("abcdefgh ij", 3), ["abc", "def", "gh", "ij"]);
+    }
+
+    #[test]
+    fn wrap_keeps_the_graphemes_whole() {
+        let family = "👨\u{200d}👩\u{200d}👧";
+        let text = format!("{}{}{}", family, family, family);
+        assert_eq!(
+            Content::wrap(&text, 2),
+            [format!("{}{}", family, family), family.to_string()]
+        );
+        assert_eq!(
+            Content::wrap("e\u{301}e\u{301}e\u{301}", 2),
+            ["e\u{301}e\u{301}", "e\u{301}"]
+        );
+    }
+}
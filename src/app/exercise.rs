@@ -1,12 +1,19 @@
-use super::{content::Content, prompt::Prompt, state::State, timer::Timer};
-use crate::app::settings::Settings;
-use chrono::{DateTime, Local};
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use serde::ser::{Serialize, SerializeStruct, Serializer};
-use std::{
-    fs::{self, OpenOptions},
-    io::Write,
+use super::{
+    bindings::{Action, KeyBindings},
+    content::Content,
+    prompt::{Prompt, PromptResult},
+    rolling::Rolling,
+    state::State,
+    timer::Timer,
+};
+use crate::{
+    app::settings::{Setting, Settings, Stop, Target},
+    record, stats,
 };
+use chrono::{DateTime, Local, SecondsFormat};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::time::{Duration, Instant};
 
 /// The main structure of the application that combines all the parts of the type trainer.
 pub struct Exercise {
@@ -16,23 +23,49 @@ pub struct Exercise {
     pub settings: Settings,
     pub prompt: Prompt,
     pub state: State,
+    pub pasted: usize, // the amount of characters that were pasted, an exercise with pasted text is invalid
+    pub next_settings: Settings, // the settings of the settings overlay, they are used from the next restart
+    pub overlay: Option<usize>,  // the selected setting while the settings overlay is open
+    pub bindings: KeyBindings,
+    pub rolling: Option<Rolling>, // the statistics of the last minutes of an endless exercise
+    pub save_error: Option<String>, // the reason the results of the finished exercise could not be saved
+    pub results: Vec<PromptResult>, // the results of the finished prompts
+    last_input: Option<Instant>,    // the moment of the last keystroke while the exercise was running
+    finished: (usize, usize, Duration), // the correct keys, the mistakes and the time at the end of the last finished prompt
 }
 
 impl Exercise {
     /// Builds a new exercise
-    pub fn build(timer: Timer, content: Content, settings: Settings) -> Exercise {
+    pub fn build(
+        timer: Timer,
+        content: Content,
+        settings: Settings,
+        bindings: KeyBindings,
+    ) -> Exercise {
         let prompt = Prompt::new(
             content
                 .get_prompt()
                 .expect("The program will not start if there is no content"),
+            settings.layout,
+            settings.stop,
+            settings.leniency,
         );
         Exercise {
             time: Local::now(),
             timer,
-            prompt: prompt,
+            prompt,
+            next_settings: settings.clone(),
+            rolling: settings.endless.map(Rolling::new),
             settings,
             content,
             state: State::Waiting,
+            pasted: 0,
+            overlay: None,
+            bindings,
+            save_error: None,
+            results: Vec::new(),
+            last_input: None,
+            finished: (0, 0, Duration::ZERO),
         }
     }
 
@@ -43,8 +76,9 @@ impl Exercise {
 
     /// Handles key events and changes the state of the application accordingly.
     ///
-    /// Any [KeyEvent] with the [KeyModifiers::CONTROL] modifier will be used to change the state of the application.
-    /// Events without this modifier will be interpreted as input for the exercise prompt.
+    /// A [KeyEvent] that is bound to an [Action] in the [KeyBindings] will be used to change the state of the application.
+    /// Other events with the [KeyModifiers::CONTROL] modifier are ignored,
+    /// the remaining events will be interpreted as input for the exercise prompt.
     ///
     /// The application will stay in [State::Waiting] until the user starts typing.
     /// The application will then progress to [State::Running].
@@ -57,50 +91,53 @@ impl Exercise {
     /// - [State::Pausing] by pressing the pause button, the application will wait in this state until the user starts typing again
     ///
     /// From [State::Finished] the user can quit the application or restart the exercise.
+    ///
+    /// In [State::Waiting] and [State::Finished], the settings overlay can also be opened.
+    /// While it is open, the keys are used to change the settings, see [Exercise::update_overlay].
     pub fn update(&mut self, key_event: KeyEvent) {
-        if key_event.kind == KeyEventKind::Press {
-            if key_event.modifiers == KeyModifiers::CONTROL {
-                match self.state {
-                    State::Waiting => match key_event.code {
-                        KeyCode::Char('c') | KeyCode::Char('C') => self.quit(),
-                        _ => {}
-                    },
-                    State::Running => match key_event.code {
-                        KeyCode::Char('c') | KeyCode::Char('C') => self.stop(),
-                        KeyCode::Char('r') | KeyCode::Char('R') => self.restart(),
-                        KeyCode::Char('p') | KeyCode::Char('P') => self.pause(),
-                        _ => {}
-                    },
-                    State::Pausing => match key_event.code {
-                        KeyCode::Char('c') | KeyCode::Char('C') => self.stop(),
-                        KeyCode::Char('r') | KeyCode::Char('R') => self.restart(),
-                        _ => {}
-                    },
-                    State::Finished => match key_event.code {
-                        KeyCode::Char('c') | KeyCode::Char('C') => self.quit(),
-                        KeyCode::Char('r') | KeyCode::Char('R') => self.restart(),
-                        _ => {}
-                    },
-                    _ => {}
-                }
-            } else {
-                if self.state == State::Waiting || self.state == State::Pausing {
-                    self.state = State::Running;
-                    self.start();
-                }
-                if self.state == State::Running {
-                    match key_event.code {
-                        KeyCode::Enter => self.press_enter(),
-                        KeyCode::Char(c) => self.press_char(c),
-                        KeyCode::Backspace => self.press_backspace(),
-                        _ => {}
-                    };
-                    if self.settings.auto && (self.prompt.prompt.len() == self.prompt.typed.len()) {
-                        self.press_enter();
+        if key_event.kind == KeyEventKind::Press && self.overlay.is_some() {
+            self.update_overlay(key_event);
+        } else if key_event.kind == KeyEventKind::Press {
+            match self.bindings.action(&key_event, self.state.actions()) {
+                Some(Action::Quit) => self.quit(),
+                Some(Action::Stop) => self.stop(),
+                Some(Action::Restart) => self.restart(),
+                Some(Action::Pause) => self.pause(),
+                Some(Action::Settings) => self.overlay = Some(0),
+                None if key_event.modifiers == KeyModifiers::CONTROL
+                    && !Self::is_delete_word(&key_event)
+                    && !Self::is_backspace(&key_event) => {}
+                None => {
+                    if self.state == State::Waiting || self.state == State::Pausing {
+                        self.state = State::Running;
+                        self.start();
                     }
-                    if let Some(t) = self.settings.terminate {
-                        if self.prompt.count_fault >= t {
-                            self.stop();
+                    if self.state == State::Running {
+                        self.last_input = Some(Instant::now());
+                        self.prompt.keys.on_keypad =
+                            key_event.state.contains(KeyEventState::KEYPAD);
+                        match key_event.code {
+                            _ if Self::is_delete_word(&key_event) => self.press_delete_word(),
+                            _ if Self::is_backspace(&key_event) => self.press_backspace(),
+                            KeyCode::Enter => self.press_enter(),
+                            KeyCode::Char(c) => self.press_char(c),
+                            _ => {}
+                        };
+                        if self.settings.auto && self.prompt.is_complete() {
+                            self.press_enter();
+                        }
+                        if let Some(rolling) = &mut self.rolling {
+                            rolling.record(self.timer.get_time(), &self.prompt);
+                        }
+                        if let Some((done, target)) = self.target_progress() {
+                            if self.state == State::Running && done >= target {
+                                self.stop();
+                            }
+                        }
+                        if let Some(t) = self.settings.terminate {
+                            if self.prompt.count_fault >= t {
+                                self.stop();
+                            }
                         }
                     }
                 }
@@ -108,10 +145,75 @@ impl Exercise {
         }
     }
 
+    /// Handles the key events while the settings overlay is open.
+    ///
+    /// The settings are selected with the arrows and changed with enter or space.
+    /// The changes are applied when the exercise is restarted,
+    /// so closing the overlay in [State::Waiting] applies them immediately.
+    fn update_overlay(&mut self, key_event: KeyEvent) {
+        let Some(selected) = self.overlay else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Up => self.overlay = Some(selected.saturating_sub(1)),
+            KeyCode::Down => self.overlay = Some(usize::min(selected + 1, Setting::ALL.len() - 1)),
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                self.next_settings.toggle(Setting::ALL[selected])
+            }
+            KeyCode::Esc => self.close_overlay(),
+            _ if self.bindings.get(Action::Settings).matches(&key_event) => self.close_overlay(),
+            _ => {}
+        }
+    }
+
+    /// Close the settings overlay.
+    fn close_overlay(&mut self) {
+        self.overlay = None;
+        if self.state == State::Waiting {
+            self.restart();
+        }
+    }
+
+    /// Handles text that was pasted in the terminal.
+    ///
+    /// Pasting text marks the exercise as invalid.
+    /// The text is ignored, unless pasting is enabled in the [Settings].
+    /// In that case every character is handled as if it was typed.
+    pub fn paste(&mut self, text: &str) {
+        if self.state == State::Finished || self.overlay.is_some() {
+            return;
+        }
+        self.pasted += text.chars().count();
+
+        if self.settings.paste {
+            for c in text.chars() {
+                let code = match c {
+                    '\n' | '\r' => KeyCode::Enter,
+                    c => KeyCode::Char(c),
+                };
+                self.update(KeyEvent::new(code, KeyModifiers::NONE));
+            }
+        }
+    }
+
+    /// Returns true if the stats of the exercise can be trusted, this is not the case when text was pasted.
+    pub fn is_valid(&self) -> bool {
+        self.pasted == 0
+    }
+
     /// Handles the press of [KeyCode::Enter]
+    ///
+    /// The prompt is not finished when the last word has a mistake in [Stop::Word] mode.
+    /// The characters that are being composed are committed first.
     fn press_enter(&mut self) {
+        self.prompt.commit();
+        if self.settings.stop == Some(Stop::Word) && self.prompt.word_failed() {
+            return;
+        }
         if !self.settings.correct || self.prompt.is_correct() {
+            let chars = self.prompt.prompt.len();
             self.prompt.finish();
+            self.record_result(chars);
 
             if !self.prompt.failed || !self.settings.repeat {
                 self.content.next_prompt();
@@ -125,13 +227,57 @@ impl Exercise {
         }
     }
 
+    /// Record the result of the prompt that was just finished, the counters of the prompt are totals of the exercise.
+    fn record_result(&mut self, chars: usize) {
+        let (correct, mistakes, time) = self.finished;
+        let now = (
+            self.prompt.count_correct,
+            self.prompt.count_fault,
+            self.timer.get_time(),
+        );
+        self.results.push(PromptResult {
+            chars,
+            correct: now.0.saturating_sub(correct),
+            mistakes: now.1.saturating_sub(mistakes),
+            time: now.2.saturating_sub(time),
+        });
+        self.finished = now;
+    }
+
     /// Handles the press of [KeyCode::Backspace]
     fn press_backspace(&mut self) {
-        if self.settings.backspace {
+        if self.settings.backspace && (!self.settings.word_backspace || self.prompt.in_word()) {
             self.prompt.remove_char();
         }
     }
 
+    /// Handles the keys that delete a word, see [Exercise::is_delete_word]
+    fn press_delete_word(&mut self) {
+        if self.settings.backspace {
+            self.prompt.remove_word(self.settings.word_backspace);
+        }
+    }
+
+    /// Returns true if the key deletes a word: Ctrl+Backspace, Alt+Backspace or Ctrl+W.
+    fn is_delete_word(key_event: &KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Backspace => key_event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT),
+            KeyCode::Char('w') => key_event.modifiers == KeyModifiers::CONTROL,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the key deletes a character: Backspace, or Ctrl+H that many terminals send for it.
+    fn is_backspace(key_event: &KeyEvent) -> bool {
+        match key_event.code {
+            KeyCode::Backspace => !Self::is_delete_word(key_event),
+            KeyCode::Char('h') => key_event.modifiers == KeyModifiers::CONTROL,
+            _ => false,
+        }
+    }
+
     /// Handles the press of any [KeyCode::Char]
     fn press_char(&mut self, c: char) {
         self.prompt.type_char(c);
@@ -146,14 +292,43 @@ impl Exercise {
         let letters = self.prompt.count_correct - self.prompt.count_correct_remove;
 
         // for the wpm calculation, each word is 5 letters long
-        let words = letters as f32 / 5 as f32;
+        let words = letters as f32 / 5.0;
 
         let seconds = self.timer.get_time().as_secs_f32();
-        let minutes = seconds / 60 as f32;
+        let minutes = seconds / 60.0;
 
         (words / minutes).round() as usize
     }
 
+    /// Returns the typed amount and the amount of the [Target], if a target is set.
+    pub fn target_progress(&self) -> Option<(usize, usize)> {
+        match self.settings.target {
+            Some(Target::Words(words)) => Some((self.prompt.words(), words)),
+            Some(Target::Chars(chars)) => Some((self.prompt.chars(), chars)),
+            None => None,
+        }
+    }
+
+    /// Returns the WPM score that is shown during the exercise.
+    ///
+    /// For an endless exercise, this is the score of the last minutes instead of the whole exercise.
+    pub fn current_wpm(&self) -> usize {
+        match &self.rolling {
+            Some(rolling) => rolling.wpm(self.timer.get_time(), &self.prompt),
+            None => self.calculate_wpm(),
+        }
+    }
+
+    /// Returns the ratio of correctly typed characters that is shown during the exercise.
+    ///
+    /// For an endless exercise, this is the ratio of the last minutes instead of the whole exercise.
+    pub fn current_ratio(&self) -> f64 {
+        match &self.rolling {
+            Some(rolling) => rolling.ratio(self.timer.get_time(), &self.prompt),
+            None => self.prompt.ratio(),
+        }
+    }
+
     /// Handles the transition to [State::Running]
     fn start(&mut self) {
         self.state = State::Running;
@@ -162,18 +337,36 @@ impl Exercise {
 
     /// Handles the transition to [State::Pausing]
     fn pause(&mut self) {
+        self.pause_at(Instant::now());
+    }
+
+    /// Handles the transition to [State::Pausing], the time after the given moment is not counted.
+    fn pause_at(&mut self, moment: Instant) {
         self.state = State::Pausing;
-        self.timer.stop();
+        self.timer.stop_at(moment);
+        self.prompt.keys.interrupt();
     }
 
     /// Handles the transition to [State::Waiting]
     fn restart(&mut self) {
         self.state = State::Waiting;
+        self.settings = self.next_settings.clone();
+        self.rolling = self.settings.endless.map(Rolling::new);
         self.time = Local::now();
+        self.pasted = 0;
+        self.save_error = None;
+        self.results.clear();
+        self.last_input = None;
+        self.finished = (0, 0, Duration::ZERO);
 
         self.content.reset();
         self.timer.reset();
-        self.prompt = Prompt::new(self.content.get_prompt().unwrap());
+        self.prompt = Prompt::new(
+            self.content.get_prompt().unwrap(),
+            self.settings.layout,
+            self.settings.stop,
+            self.settings.leniency,
+        );
     }
 
     /// Handles the transition to [State::Finished]
@@ -195,30 +388,57 @@ impl Exercise {
         }
     }
 
-    /// Saves the stats and the settings of the [Exercise] in a .json file in the cache directory.
-    fn save(&self) {
-        if let Ok(mut s) = serde_json::to_string(self) {
-            s.push('\n');
-
-            let mut path = dirs::cache_dir().expect("This should return the standard cache path");
-            path.push("type_trainer");
+    /// Handles the loss of focus of the terminal, a running exercise is paused.
+    pub fn focus_lost(&mut self) {
+        if self.state == State::Running {
+            self.pause();
+        }
+    }
 
-            if !path.exists() {
-                let _ = fs::create_dir(&path);
-            }
+    /// Returns the moment the user becomes idle,
+    /// if the exercise is running and the idle timeout is set in the [Settings].
+    pub fn idle_deadline(&self) -> Option<Instant> {
+        match self.state {
+            State::Running => Some(self.last_input? + self.settings.idle?),
+            _ => None,
+        }
+    }
 
-            path.push("stats.json");
-            let file = OpenOptions::new()
-                .create(true)
-                .append(true)
-                .write(true)
-                .open(path);
+    /// Pause the exercise when the user is idle.
+    ///
+    /// The timer is stopped at the moment of the last keystroke, so the idle time does not lower the WPM.
+    pub fn check_idle(&mut self) {
+        self.check_idle_at(Instant::now());
+    }
 
-            if let Ok(mut file) = file {
-                if let Ok(_) = file.write(s.as_bytes()) {}
+    /// Pause the exercise when the user is idle at the given moment.
+    fn check_idle_at(&mut self, now: Instant) {
+        if let (Some(deadline), Some(last_input)) = (self.idle_deadline(), self.last_input) {
+            if now >= deadline {
+                self.pause_at(last_input);
             }
         }
     }
+
+    /// Returns the next moment the exercise needs attention without any input from the user:
+    /// when the [Timer] expires or its label changes, or when the user becomes idle.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match (self.timer.next_deadline(), self.idle_deadline()) {
+            (Some(timer), Some(idle)) => Some(Instant::min(timer, idle)),
+            (timer, idle) => timer.or(idle),
+        }
+    }
+
+    /// Saves the stats and the settings of the [Exercise] in the stats file.
+    ///
+    /// A failure is kept to show it on the finish screen, the results are not lost without notice.
+    fn save(&mut self) {
+        self.save_error = serde_json::to_string(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|record| stats::append(&record))
+            .err()
+            .map(|e| e.to_string());
+    }
 }
 
 /// Serializes the [Exercise] to a .json string
@@ -227,13 +447,91 @@ impl Serialize for Exercise {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Exercise", 6)?;
-        state.serialize_field("date", &self.time.to_rfc2822())?;
+        let mut state = serializer.serialize_struct("Exercise", 10)?;
+        state.serialize_field("version", &record::VERSION)?;
+        state.serialize_field("date", &self.time.to_rfc3339_opts(SecondsFormat::Secs, false))?;
         state.serialize_field("timer", &self.timer)?;
         state.serialize_field("content", &self.content)?;
         state.serialize_field("settings", &self.settings)?;
         state.serialize_field("stats", &self.prompt)?;
+        state.serialize_field("prompts", &self.results)?;
         state.serialize_field("wpm", &self.calculate_wpm())?;
+        state.serialize_field("valid", &self.is_valid())?;
+        state.serialize_field("pasted", &self.pasted)?;
         state.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::content::Split;
+    use std::path::PathBuf;
+
+    fn exercise(idle: Option<Duration>) -> Exercise {
+        let content = Content::build(
+            PathBuf::from("test"),
+            vec![String::from("abc")],
+            false,
+            Split::Line,
+            false,
+        );
+        let settings = Settings {
+            backspace: true,
+            highlight: true,
+            idle,
+            ..Settings::default()
+        };
+        Exercise::build(Timer::new(None), content, settings, KeyBindings::default())
+    }
+
+    #[test]
+    fn idle_time_is_not_counted() {
+        const IDLE: Duration = Duration::from_secs(60);
+        let mut exercise = exercise(Some(IDLE));
+        exercise.update(KeyEvent::from(KeyCode::Char('a')));
+        let last_input = exercise.last_input.unwrap();
+
+        exercise.check_idle_at(last_input + IDLE / 2);
+        assert!(exercise.state == State::Running);
+
+        // The minute without input is not counted
+        exercise.check_idle_at(last_input + IDLE * 2);
+        assert!(exercise.state == State::Pausing);
+        assert!(exercise.timer.get_time() < IDLE / 2);
+    }
+
+    fn typed(exercise: &Exercise) -> String {
+        exercise.prompt.typed.concat()
+    }
+
+    #[test]
+    fn ctrl_h_deletes_a_character() {
+        let mut exercise = exercise(None);
+        for c in "ab cd".chars() {
+            exercise.update(KeyEvent::from(KeyCode::Char(c)));
+        }
+        exercise.update(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::CONTROL));
+        assert_eq!(typed(&exercise), "ab c");
+        exercise.update(KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(typed(&exercise), "ab ");
+    }
+
+    #[test]
+    fn delete_word_keys() {
+        let mut exercise = exercise(None);
+        for key in [
+            KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Backspace, KeyModifiers::CONTROL),
+            KeyEvent::new(KeyCode::Backspace, KeyModifiers::ALT),
+        ] {
+            for c in "ab cd".chars() {
+                exercise.update(KeyEvent::from(KeyCode::Char(c)));
+            }
+            exercise.update(key);
+            assert_eq!(typed(&exercise), "ab ");
+            exercise.update(key);
+            assert_eq!(typed(&exercise), "");
+        }
+    }
+}
@@ -0,0 +1,799 @@
+use std::path::Path;
+
+/// The comment syntax of a programming language.
+pub struct Syntax {
+    extensions: &'static [&'static str],
+    line: &'static [&'static str], // the prefixes of a comment until the end of the line
+    block: &'static [(&'static str, &'static str)], // the delimiters of a comment over multiple lines, or a docstring
+    quotes: &'static [char], // the quotes of the string literals, a comment cannot start in a string
+}
+
+/// The languages of which the comments or the code can be extracted, the first one is used when the extension is unknown.
+const SYNTAXES: [Syntax; 9] = [
+    Syntax {
+        extensions: &[
+            "c", "h", "cc", "cpp", "cxx", "hpp", "cs", "java", "kt", "kts", "scala", "swift", "go",
+            "dart", "js", "mjs", "jsx", "ts", "tsx", "php",
+        ],
+        line: &["//"],
+        block: &[("/*", "*/")],
+        quotes: &['"', '\'', '`'],
+    },
+    Syntax {
+        extensions: &["rs"],
+        line: &["//"],
+        block: &[("/*", "*/")],
+        quotes: &['"'],
+    },
+    Syntax {
+        extensions: &["py", "pyi"],
+        line: &["#"],
+        block: &[("\"\"\"", "\"\"\""), ("'''", "'''")],
+        quotes: &['"', '\''],
+    },
+    Syntax {
+        extensions: &[
+            "sh", "bash", "zsh", "fish", "rb", "pl", "r", "toml", "yaml", "yml", "conf",
+        ],
+        line: &["#"],
+        block: &[],
+        quotes: &['"', '\''],
+    },
+    Syntax {
+        extensions: &["lua"],
+        line: &["--"],
+        block: &[("--[[", "]]")],
+        quotes: &['"', '\''],
+    },
+    Syntax {
+        extensions: &["hs"],
+        line: &["--"],
+        block: &[("{-", "-}")],
+        quotes: &['"'],
+    },
+    Syntax {
+        extensions: &["sql"],
+        line: &["--"],
+        block: &[("/*", "*/")],
+        quotes: &['"', '\''],
+    },
+    Syntax {
+        extensions: &["css"],
+        line: &[],
+        block: &[("/*", "*/")],
+        quotes: &['"', '\''],
+    },
+    Syntax {
+        extensions: &["scss"],
+        line: &["//"],
+        block: &[("/*", "*/")],
+        quotes: &['"', '\''],
+    },
+];
+
+impl Syntax {
+    /// Returns the syntax of the language of the file, by the extension of the file.
+    fn from_path(path: &Path) -> Option<&'static Syntax> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        SYNTAXES
+            .iter()
+            .find(|syntax| syntax.extensions.contains(&extension.as_str()))
+    }
+}
+
+/// The format of the file, the text to type is extracted from it before it is split into prompts.
+#[derive(Clone, Copy)]
+pub enum Format {
+    Plain,
+    Markdown,
+    Html,
+    Source(&'static Syntax),
+}
+
+impl Format {
+    /// The names of the formats in the command line arguments, "auto" selects the format by the extension.
+    pub const NAMES: [&'static str; 5] = ["auto", "plain", "markdown", "html", "source"];
+
+    /// Returns the format of the file, by the extension of the file.
+    pub fn from_path(path: &Path) -> Format {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("md" | "markdown" | "mdown" | "mkd") => Format::Markdown,
+            Some("html" | "htm" | "xhtml") => Format::Html,
+            _ => Syntax::from_path(path).map_or(Format::Plain, Format::Source),
+        }
+    }
+
+    /// Returns the format with the given name, the language of a source file is still found by the extension.
+    pub fn from_name(name: &str, path: &Path) -> Option<Format> {
+        match name {
+            "auto" => Some(Format::from_path(path)),
+            "plain" => Some(Format::Plain),
+            "markdown" => Some(Format::Markdown),
+            "html" => Some(Format::Html),
+            "source" => Some(Format::Source(
+                Syntax::from_path(path).unwrap_or(&SYNTAXES[0]),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the text of the file that is typed, the paragraphs are separated by empty lines.
+    pub fn extract(&self, text: &str, extract: Extract) -> String {
+        match self {
+            Format::Plain => text.to_string(),
+            Format::Markdown => markdown(text, extract),
+            Format::Html => html(text),
+            Format::Source(syntax) => source(text, syntax, extract),
+        }
+    }
+}
+
+/// The part of a Markdown or a source file that is extracted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Extract {
+    Text, // the text without the code blocks, or the comments and docstrings of a source file
+    Code, // only the code blocks, or the code of a source file without the comments
+    All,
+}
+
+impl Extract {
+    /// The names of the parts in the command line arguments.
+    pub const NAMES: [&'static str; 3] = ["text", "code", "all"];
+
+    /// Returns the part with the given name.
+    pub fn from_name(name: &str) -> Option<Extract> {
+        match name {
+            "text" => Some(Extract::Text),
+            "code" => Some(Extract::Code),
+            "all" => Some(Extract::All),
+            _ => None,
+        }
+    }
+}
+
+/// Strip the markup of a Markdown text.
+///
+/// Headings, list items and table rows become separate paragraphs,
+/// the links and images are replaced by their text and the fenced code blocks are kept verbatim.
+fn markdown(text: &str, extract: Extract) -> String {
+    let mut output = String::new();
+    let mut fence: Option<&str> = None; // the marker of the open code block
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+                output.push('\n');
+            } else if extract != Extract::Text {
+                output.push_str(line);
+                output.push('\n');
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            output.push('\n');
+            continue;
+        }
+        if extract == Extract::Code {
+            continue;
+        }
+
+        let mut line = trimmed;
+        while let Some(quoted) = line.strip_prefix('>') {
+            line = quoted.trim_start();
+        }
+
+        // Rules, setext underlines, table separators and link definitions are not typed
+        let rule = line.len() >= 3
+            && (line.chars().all(|c| "-* ".contains(c))
+                || line.chars().all(|c| "_ ".contains(c))
+                || line.chars().all(|c| c == '='));
+        let separator = line.starts_with('|') && line.chars().all(|c| "|-: ".contains(c));
+        let definition =
+            line.starts_with('[') && line.find("]:").is_some_and(|i| !line[..i].contains(']'));
+        if line.is_empty() || rule || separator || definition {
+            output.push('\n');
+            continue;
+        }
+
+        let block = if line.starts_with('#') {
+            line = line.trim_start_matches('#').trim_end_matches('#').trim();
+            true
+        } else if line.starts_with('|') {
+            true
+        } else if let Some(item) = list_item(line) {
+            line = item;
+            true
+        } else {
+            false
+        };
+
+        let stripped = markdown_inline(line);
+        let stripped = match line.starts_with('|') {
+            true => stripped
+                .split('|')
+                .map(|cell| cell.trim())
+                .filter(|cell| !cell.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" "),
+            false => stripped,
+        };
+        if block {
+            output.push('\n');
+        }
+        output.push_str(&stripped);
+        output.push('\n');
+        if block {
+            output.push('\n');
+        }
+    }
+    output
+}
+
+/// Returns the text of a list item without the bullet, the number or the checkbox.
+fn list_item(line: &str) -> Option<&str> {
+    let item = match line.strip_prefix(['-', '*', '+']) {
+        Some(item) => item,
+        None => {
+            let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            match digits {
+                0 => return None,
+                _ => line[digits..].strip_prefix(['.', ')'])?,
+            }
+        }
+    };
+    if !item.starts_with(' ') {
+        return None;
+    }
+    let item = item.trim_start();
+    Some(
+        ["[ ] ", "[x] ", "[X] "]
+            .into_iter()
+            .find_map(|checkbox| item.strip_prefix(checkbox))
+            .unwrap_or(item),
+    )
+}
+
+/// Strip the inline markup of a line of Markdown: emphasis, code spans, links, images and html tags.
+fn markdown_inline(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let find = |from: usize, c: char| chars[from..].iter().position(|&x| x == c).map(|i| from + i);
+    let mut output = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let previous = i.checked_sub(1).map(|i| chars[i]);
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\\' if next.is_some_and(|n| n.is_ascii_punctuation()) => {
+                output.extend(next);
+                i += 2;
+            }
+            '`' => {
+                let run = chars[i..].iter().take_while(|&&x| x == '`').count();
+                let close = (i + run..chars.len()).find(|&j| {
+                    chars[j..].iter().take_while(|&&x| x == '`').count() == run
+                        && chars.get(j.wrapping_sub(1)) != Some(&'`')
+                });
+                match close {
+                    Some(close) => {
+                        output.extend(chars[i + run..close].iter());
+                        i = close + run;
+                    }
+                    None => {
+                        output.extend(chars[i..i + run].iter());
+                        i += run;
+                    }
+                }
+            }
+            '!' | '[' if c == '[' || next == Some('[') => {
+                let open = if c == '!' { i + 1 } else { i };
+                let link = find(open, ']').and_then(|close| match chars.get(close + 1) {
+                    Some('(') => find(close + 1, ')').map(|end| (close, end)),
+                    Some('[') => find(close + 1, ']').map(|end| (close, end)),
+                    _ => None,
+                });
+                match link {
+                    Some((close, end)) => {
+                        let label: String = chars[open + 1..close].iter().collect();
+                        output.push_str(&markdown_inline(&label));
+                        i = end + 1;
+                    }
+                    None => {
+                        output.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '<' if next.is_some_and(|n| n.is_ascii_alphabetic() || n == '/' || n == '!') => {
+                match find(i, '>') {
+                    Some(close) => {
+                        let tag: String = chars[i + 1..close].iter().collect();
+                        // An autolink shows its address
+                        if tag.contains("://") || tag.contains('@') {
+                            output.push_str(&tag);
+                        }
+                        i = close + 1;
+                    }
+                    None => {
+                        output.push(c);
+                        i += 1;
+                    }
+                }
+            }
+            '*' | '~' | '_' => {
+                let run = chars[i..].iter().take_while(|&&x| x == c).count();
+                let after = chars.get(i + run).copied();
+                let word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+                let space = |c: Option<char>| c.map_or(true, |c| c.is_whitespace());
+                // An underscore within a word, or a lone asterisk between spaces, is not emphasis
+                let literal = match c {
+                    '_' => word(previous) && word(after),
+                    '~' => run == 1,
+                    _ => space(previous) && space(after),
+                };
+                if literal {
+                    output.extend(chars[i..i + run].iter());
+                }
+                i += run;
+            }
+            _ => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+    decode_entities(&output)
+}
+
+/// Keep the text of an html document, without the tags, the scripts and the styles.
+///
+/// The whitespace is collapsed like a browser does, and the block elements become separate paragraphs.
+fn html(text: &str) -> String {
+    const BLOCKS: [&str; 20] = [
+        "p",
+        "div",
+        "br",
+        "li",
+        "tr",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+        "section",
+        "article",
+        "header",
+        "footer",
+        "blockquote",
+        "pre",
+        "table",
+        "ul",
+        "ol",
+    ];
+
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        push_collapsed(&mut output, &rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+        let tag = rest[1..end].trim_end_matches('>').to_lowercase();
+        rest = &rest[end..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+        if !closing && (name == "script" || name == "style") {
+            let close = format!("</{}", name);
+            rest = match find_ignore_ascii_case(rest, &close) {
+                Some(i) => rest[i..].find('>').map_or("", |end| &rest[i + end + 1..]),
+                None => "",
+            };
+        } else if BLOCKS.contains(&name.as_str()) {
+            output.push_str("\n\n");
+        } else if name == "td" || name == "th" {
+            output.push(' ');
+        }
+    }
+    push_collapsed(&mut output, rest);
+    decode_entities(&output)
+}
+
+/// Returns the position of the ASCII pattern in the text, the case of the letters is ignored.
+///
+/// The text itself is not converted, because a lowercase letter can have another length in bytes.
+fn find_ignore_ascii_case(text: &str, pattern: &str) -> Option<usize> {
+    text.as_bytes()
+        .windows(pattern.len())
+        .position(|window| window.eq_ignore_ascii_case(pattern.as_bytes()))
+}
+
+/// Append the text to the output with every run of whitespace replaced by a single space.
+fn push_collapsed(output: &mut String, text: &str) {
+    let mut space = output.ends_with(char::is_whitespace);
+    for c in text.chars() {
+        match c.is_whitespace() {
+            true if !space => output.push(' '),
+            true => {}
+            false => output.push(c),
+        }
+        space = c.is_whitespace();
+    }
+}
+
+/// Replace the html entities by their characters, a non-breaking space becomes a normal space.
+fn decode_entities(text: &str) -> String {
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&end| end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let decoded = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            "ndash" => Some('–'),
+            "mdash" => Some('—'),
+            "hellip" => Some('…'),
+            "lsquo" => Some('‘'),
+            "rsquo" => Some('’'),
+            "ldquo" => Some('“'),
+            "rdquo" => Some('”'),
+            _ => {
+                let number = entity.strip_prefix('#')?;
+                let code = match number.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => number.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, decoded) {
+            (Some(entity), Some(c)) => {
+                output.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                output.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Separate the comments and the code of a source file.
+///
+/// The markers of the comments, like the slashes of a doc comment or the asterisks in a block comment, are removed.
+/// The comments that are separated by code or an empty line become separate paragraphs.
+fn source(text: &str, syntax: &Syntax, extract: Extract) -> String {
+    if extract == Extract::All {
+        return text.to_string();
+    }
+
+    let mut comments = String::new();
+    let mut code = String::new();
+    let mut separated = false; // whether there is code or an empty line since the last comment
+    let mut newlines = 0;
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        if let Some((open, close)) = syntax.block.iter().find(|(open, _)| rest.starts_with(open)) {
+            let body = &rest[open.len()..];
+            let end = body.find(close).unwrap_or(body.len());
+            push_comment(&mut comments, &body[..end], &mut separated);
+            rest = &body[usize::min(end + close.len(), body.len())..];
+            newlines = 0;
+        } else if let Some(prefix) = syntax.line.iter().find(|prefix| {
+            rest.starts_with(*prefix) && !(rest.starts_with("#!") && code.is_empty())
+        }) {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            push_comment(&mut comments, &rest[prefix.len()..end], &mut separated);
+            rest = &rest[end..];
+            newlines = 0;
+        } else if syntax.quotes.contains(&c) {
+            // A string literal is code, up to the quote that is not escaped
+            let mut end = c.len_utf8();
+            let mut escaped = false;
+            for x in rest[end..].chars() {
+                end += x.len_utf8();
+                match x {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    _ if x == c => break,
+                    _ => {}
+                }
+            }
+            code.push_str(&rest[..end]);
+            rest = &rest[end..];
+            separated = true;
+        } else if let Some(literal) = char_literal(rest) {
+            code.push_str(literal);
+            rest = &rest[literal.len()..];
+            separated = true;
+        } else {
+            match c {
+                '\n' => {
+                    newlines += 1;
+                    separated |= newlines >= 2;
+                }
+                c if !c.is_whitespace() => separated = true,
+                _ => {}
+            }
+            code.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    match extract {
+        Extract::Code => code,
+        _ => comments,
+    }
+}
+
+/// Append the lines of a comment without their markers, after an empty line if the comment is separated from the previous one.
+fn push_comment(comments: &mut String, comment: &str, separated: &mut bool) {
+    if *separated {
+        comments.push('\n');
+        *separated = false;
+    }
+    for line in comment.lines() {
+        let line = line.trim().trim_start_matches(['/', '*', '!', '#']).trim();
+        if !line.is_empty() {
+            comments.push_str(line);
+            comments.push('\n');
+        }
+    }
+}
+
+/// Returns the character literal at the start of the text, like 'a' or '\n',
+/// so the quote in '"' does not start a string.
+fn char_literal(text: &str) -> Option<&str> {
+    let mut chars = text.char_indices();
+    if chars.next()?.1 != '\'' {
+        return None;
+    }
+    let (_, c) = chars.next()?;
+    if c == '\\' {
+        chars.next()?;
+    }
+    match chars.next()? {
+        (i, '\'') => Some(&text[..=i]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the paragraphs of an extracted text, with the lines of a paragraph joined by a space.
+    fn paragraphs(text: &str) -> Vec<String> {
+        text.split("\n\n")
+            .map(|paragraph| {
+                paragraph
+                    .split_whitespace()
+                    .collect::<Vec<&str>>()
+                    .join(" ")
+            })
+            .filter(|paragraph| !paragraph.is_empty())
+            .collect()
+    }
+
+    fn source_file(name: &str, text: &str, extract: Extract) -> Vec<String> {
+        paragraphs(&Format::from_path(Path::new(name)).extract(text, extract))
+    }
+
+    #[test]
+    fn format_by_extension() {
+        assert!(matches!(
+            Format::from_path(Path::new("a.MD")),
+            Format::Markdown
+        ));
+        assert!(matches!(
+            Format::from_path(Path::new("a.htm")),
+            Format::Html
+        ));
+        assert!(matches!(
+            Format::from_path(Path::new("a.rs")),
+            Format::Source(_)
+        ));
+        assert!(matches!(
+            Format::from_path(Path::new("a.txt")),
+            Format::Plain
+        ));
+        assert!(matches!(
+            Format::from_path(Path::new("README")),
+            Format::Plain
+        ));
+    }
+
+    #[test]
+    fn markdown_text() {
+        let text = "# The *title* #\n\
+                    \n\
+                    Some **bold** and `code` with a [link](https://example.com) and ![an image](a.png).\n\
+                    A snake_case word, 2 * 3 and an escaped \\*star\\*.\n\
+                    \n\
+                    ---\n\
+                    > A quote\n\
+                    - [x] A task\n\
+                    1. A number\n\
+                    \n\
+                    | A | B |\n\
+                    |---|:-:|\n\
+                    | c | d |\n\
+                    \n\
+                    [link]: https://example.com\n\
+                    ```rust\n\
+                    let x = 1;\n\
+                    ```\n";
+        assert_eq!(
+            paragraphs(&markdown(text, Extract::Text)),
+            [
+                "The title",
+                "Some bold and code with a link and an image. A snake_case word, 2 * 3 and an escaped *star*.",
+                "A quote",
+                "A task",
+                "A number",
+                "A B",
+                "c d",
+            ]
+        );
+        assert_eq!(paragraphs(&markdown(text, Extract::Code)), ["let x = 1;"]);
+    }
+
+    #[test]
+    fn markdown_inline_markup() {
+        assert_eq!(markdown_inline("``a ` b``"), "a ` b");
+        assert_eq!(
+            markdown_inline("<https://example.com> <b>bold</b>"),
+            "https://example.com bold"
+        );
+        assert_eq!(markdown_inline("~~gone~~ a ~ b"), "gone a ~ b");
+        assert_eq!(markdown_inline("[a *b*][ref]"), "a b");
+        assert_eq!(
+            markdown_inline("Tom &amp; Jerry&#33; &#x41;&unknown;"),
+            "Tom & Jerry! A&unknown;"
+        );
+    }
+
+    #[test]
+    fn html_text() {
+        let text = "<html><head><title>Title</title>\n\
+                    <STYLE>p { color: red; }</Style>\n\
+                    <script type=\"text/javascript\">if (a < b) {}</SCRIPT></head>\n\
+                    <body><h1>A   heading</h1><!-- a comment --><p>A\n  paragraph&nbsp;with <b>bold</b> text.</p>\n\
+                    <table><tr><td>a</td><td>b</td></tr></table></body></html>";
+        assert_eq!(
+            paragraphs(&html(text)),
+            ["Title", "A heading", "A paragraph with bold text.", "a b"]
+        );
+    }
+
+    #[test]
+    fn html_with_text_that_changes_length_in_lowercase() {
+        // The lowercase of İ is one byte longer, the positions in the lowercase text are different
+        let text = format!(
+            "<script>let a = '{}';</script><p>Text after the script</p>",
+            "İ".repeat(20)
+        );
+        assert_eq!(paragraphs(&html(&text)), ["Text after the script"]);
+
+        let text = format!("<style>/* {} */</style><p>Ⱥ text</p>", "İ".repeat(9));
+        assert_eq!(paragraphs(&html(&text)), ["Ⱥ text"]);
+    }
+
+    #[test]
+    fn html_without_closing_tags() {
+        assert_eq!(paragraphs(&html("<p>Open <script>no end")), ["Open"]);
+        assert_eq!(paragraphs(&html("a <b")), ["a"]);
+    }
+
+    #[test]
+    fn rust_comments_and_code() {
+        let text = "//! The crate.\n\
+                    \n\
+                    /// Returns the \"answer\".\n\
+                    /// Always.\n\
+                    fn answer() -> &'static str {\n    \
+                        let quote = '\"'; // a quote\n    \
+                        \"// not a comment\"\n\
+                    }\n\
+                    /* A block\n * comment */\n";
+        assert_eq!(
+            source_file("a.rs", text, Extract::Text),
+            [
+                "The crate.",
+                "Returns the \"answer\". Always.",
+                "a quote",
+                "A block comment"
+            ]
+        );
+        let code = source_file("a.rs", text, Extract::Code).join(" ");
+        assert!(code.contains("\"// not a comment\""), "{}", code);
+        assert!(code.contains("let quote = '\"';"), "{}", code);
+        assert!(!code.contains("The crate"), "{}", code);
+    }
+
+    #[test]
+    fn python_docstrings_and_shebang() {
+        let text = "#!/usr/bin/env python\n\
+                    # A comment\n\
+                    def f():\n    \
+                        \"\"\"A docstring.\"\"\"\n    \
+                        return '#'\n";
+        assert_eq!(
+            source_file("a.py", text, Extract::Text),
+            ["A comment", "A docstring."]
+        );
+        let code = source_file("a.py", text, Extract::Code).join(" ");
+        assert!(code.starts_with("#!/usr/bin/env python"), "{}", code);
+        assert!(code.contains("return '#'"), "{}", code);
+    }
+
+    #[test]
+    fn css_custom_properties_are_code() {
+        let text = ":root {\n  --main-color: red; /* the accent */\n}\n";
+        assert_eq!(source_file("a.css", text, Extract::Text), ["the accent"]);
+        let code = source_file("a.css", text, Extract::Code).join(" ");
+        assert!(code.contains("--main-color: red;"), "{}", code);
+    }
+
+    #[test]
+    fn scss_line_comments() {
+        let text = "// Variables\n$main: red;\n/* A block */\na { color: $main; }\n";
+        assert_eq!(
+            source_file("a.scss", text, Extract::Text),
+            ["Variables", "A block"]
+        );
+        let code = source_file("a.scss", text, Extract::Code).join(" ");
+        assert_eq!(code, "$main: red; a { color: $main; }");
+    }
+
+    #[test]
+    fn sql_and_lua_comments() {
+        assert_eq!(
+            source_file("a.sql", "SELECT 1; -- one\n", Extract::Text),
+            ["one"]
+        );
+        assert_eq!(
+            source_file(
+                "a.lua",
+                "--[[ A block ]]\nlocal a = 1 -- one\n",
+                Extract::Text
+            ),
+            ["A block", "one"]
+        );
+    }
+
+    #[test]
+    fn all_keeps_the_file() {
+        let text = "// A comment\nfn main() {}\n";
+        assert_eq!(source(text, &SYNTAXES[1], Extract::All), text);
+    }
+}
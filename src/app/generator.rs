@@ -0,0 +1,145 @@
+use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng, Rng};
+
+/// An exercise that is generated instead of read from a file, so every exercise has new prompts.
+pub struct Generator {
+    pub name: &'static str,
+    pub description: &'static str,
+    line: fn(&mut ThreadRng) -> String,
+}
+
+/// All the generators, from numbers for data entry to the symbols of programming languages.
+pub const GENERATORS: [Generator; 3] = [
+    Generator {
+        name: "keypad",
+        description: "Generated numbers for data entry on the numeric keypad",
+        line: numbers,
+    },
+    Generator {
+        name: "separators",
+        description: "Generated amounts with decimal and thousand separators",
+        line: separators,
+    },
+    Generator {
+        name: "symbols",
+        description: "Generated code with brackets, operators and punctuation",
+        line: symbols,
+    },
+];
+
+impl Generator {
+    /// Find a generator by its name.
+    pub fn from_name(name: &str) -> Option<&'static Generator> {
+        GENERATORS.iter().find(|generator| generator.name == name)
+    }
+
+    /// Generate a text with the given amount of lines, it is split into prompts like the text of a file.
+    pub fn text(&self, amount: usize) -> String {
+        let mut rng = thread_rng();
+        (0..amount)
+            .map(|_| (self.line)(&mut rng))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Returns a number with the given amount of digits, without leading zeros.
+fn digits(rng: &mut ThreadRng, length: u32) -> u64 {
+    match length {
+        0 | 1 => rng.gen_range(0..10),
+        _ => rng.gen_range(10u64.pow(length - 1)..10u64.pow(length)),
+    }
+}
+
+/// A line of whole numbers of different lengths.
+fn numbers(rng: &mut ThreadRng) -> String {
+    (0..6)
+        .map(|_| {
+            let length = rng.gen_range(1..=6);
+            digits(rng, length).to_string()
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// A line of amounts with a comma between the thousands and a decimal point, some of them negative.
+fn separators(rng: &mut ThreadRng) -> String {
+    (0..5)
+        .map(|_| {
+            let length = rng.gen_range(1..=7);
+            let whole = digits(rng, length).to_string();
+            let mut amount = String::new();
+            if rng.gen_bool(0.15) {
+                amount.push('-');
+            }
+            for (i, c) in whole.chars().enumerate() {
+                if i > 0 && (whole.len() - i) % 3 == 0 {
+                    amount.push(',');
+                }
+                amount.push(c);
+            }
+            if rng.gen_bool(0.7) {
+                let decimals = rng.gen_range(1..=3);
+                amount.push('.');
+                for _ in 0..decimals {
+                    amount.push(char::from(b'0' + rng.gen_range(0..10)));
+                }
+            }
+            amount
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// The statements of the symbol drill, the @ is replaced by a name and the # by a number.
+const TEMPLATES: [&str; 16] = [
+    "if (@ <= @) { @[#] = @; }",
+    "while (@ != @ && !@) { @++; }",
+    "@ = @ || (@ && @);",
+    "let @: Vec<@> = vec![#, #, #];",
+    "fn @(@: &[@]) -> Option<@> { None }",
+    "@ |= @ << #; @ &= ~@ >> #;",
+    "@ := @[#:#] + @[#:];",
+    "return @ > # ? @ : -@;",
+    "@[\"@\"] = {@: #, @: [#, #]};",
+    "<@ @=\"@\">{@}</@>",
+    "(@ + @) * (@ - #) / # % #;",
+    "match @ { @ => @, _ => () }",
+    "@::@(&mut @, *@);",
+    "$@ = @->@ ?? '@';",
+    "for (@ = #; @ < #; @ += #) {}",
+    "#[@(@ = \"@\")]",
+];
+
+/// The names that fill in the templates of the symbol drill.
+const NAMES: [&str; 16] = [
+    "x", "y", "i", "foo", "bar", "len", "idx", "key", "val", "map", "item", "node", "buf", "sum",
+    "count", "name",
+];
+
+/// A line of code with a lot of symbols.
+fn symbols(rng: &mut ThreadRng) -> String {
+    let template = TEMPLATES.choose(rng).expect("There are templates");
+    template
+        .chars()
+        .map(|c| match c {
+            '@' => NAMES.choose(rng).expect("There are names").to_string(),
+            '#' => rng.gen_range(0..100).to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::builtin::BUILTINS;
+
+    #[test]
+    fn names_differ_from_the_builtin_exercises() {
+        // The name is saved as the path of the exercise, a shared name would merge their statistics
+        let names: Vec<&str> = BUILTINS.iter().map(|builtin| builtin.name).collect();
+        for generator in &GENERATORS {
+            assert!(!names.contains(&generator.name));
+        }
+    }
+}
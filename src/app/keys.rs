@@ -0,0 +1,378 @@
+use super::layout::{Finger, Hand, Layout};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+/// The total of a number of measured intervals between keystrokes.
+#[derive(Clone, Copy, Default)]
+pub struct Interval {
+    pub count: usize,
+    pub total: Duration,
+}
+
+impl Interval {
+    /// Add a measured interval.
+    pub fn add(&mut self, interval: Duration) {
+        self.count += 1;
+        self.total += interval;
+    }
+
+    /// Combine the measurements of two intervals.
+    pub fn merge(&mut self, other: &Interval) {
+        self.count += other.count;
+        self.total += other.total;
+    }
+
+    /// Returns the average interval in milliseconds, or [None] if nothing was measured.
+    pub fn average_ms(&self) -> Option<u64> {
+        match self.count {
+            0 => None,
+            count => Some((self.total.as_millis() / count as u128) as u64),
+        }
+    }
+}
+
+impl Serialize for Interval {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Interval", 2)?;
+        state.serialize_field("count", &self.count)?;
+        state.serialize_field("average_ms", &self.average_ms())?;
+        state.end()
+    }
+}
+
+/// The counters of a single key, or of a group of keys.
+///
+/// Mistakes are counted on the key that should have been typed.
+#[derive(Clone, Copy, Default)]
+pub struct KeyRecord {
+    pub count_correct: usize,
+    pub count_fault: usize,
+    pub speed: Interval, // the time it took to correctly type the key after the previous keystroke
+}
+
+impl KeyRecord {
+    /// Count a keystroke, the interval is only measured for correct keystrokes.
+    pub fn add(&mut self, correct: bool, interval: Option<Duration>) {
+        match correct {
+            true => {
+                self.count_correct += 1;
+                if let Some(interval) = interval {
+                    self.speed.add(interval);
+                }
+            }
+            false => self.count_fault += 1,
+        }
+    }
+
+    /// Move a counted keystroke to the other counter, when it turned out to be correct or wrong afterwards.
+    ///
+    /// A counter that is already 0 is not lowered, the keystroke is then left as it was counted.
+    pub fn amend(&mut self, correct: bool) {
+        let (from, to) = match correct {
+            true => (&mut self.count_fault, &mut self.count_correct),
+            false => (&mut self.count_correct, &mut self.count_fault),
+        };
+        if let Some(count) = from.checked_sub(1) {
+            *from = count;
+            *to += 1;
+        }
+    }
+
+    /// Combine the counters of two records.
+    pub fn merge(&mut self, other: &KeyRecord) {
+        self.count_correct += other.count_correct;
+        self.count_fault += other.count_fault;
+        self.speed.merge(&other.speed);
+    }
+}
+
+impl Serialize for KeyRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("KeyRecord", 4)?;
+        state.serialize_field("count_correct", &self.count_correct)?;
+        state.serialize_field("count_fault", &self.count_fault)?;
+        state.serialize_field("count_timed", &self.speed.count)?;
+        state.serialize_field("average_ms", &self.speed.average_ms())?;
+        state.end()
+    }
+}
+
+/// Collects the statistics of every key while the user is typing,
+/// and uses the [Layout] to aggregate them by [Finger] and [Hand].
+///
+/// The transitions between the keys are recorded as bigrams and trigrams.
+/// These are only timed when the previous keys were typed correctly right before,
+/// a mistake is counted on the n-grams that end in the key that should have been typed.
+pub struct KeyStats {
+    pub layout: Layout,
+    pub keys: BTreeMap<String, KeyRecord>,
+    pub bigrams: BTreeMap<String, KeyRecord>,
+    pub trigrams: BTreeMap<String, KeyRecord>,
+    pub same_finger: Interval, // consecutive correct keys typed with the same finger
+    pub same_hand: Interval, // consecutive correct keys typed with different fingers of the same hand
+    pub alternation: Interval, // consecutive correct keys typed with alternating hands
+    pub keypad: BTreeMap<String, KeyRecord>, // the keys typed on the numeric keypad instead of the main keyboard
+    pub on_keypad: bool, // the next character is typed on the numeric keypad
+
+    last: Option<Instant>, // the time of the previous keystroke
+    recent: Vec<(String, Instant)>, // the last consecutive correct characters, at most two
+    recorded: Option<Recorded>, // the last recorded character, so it can be amended
+}
+
+/// The records that a typed character was counted in.
+struct Recorded {
+    keys: Vec<String>, // the key, followed by the bigram and the trigram that end in it
+    keypad: bool,      // the key was counted in the keys of the numeric keypad
+    correct: bool,
+}
+
+impl KeyStats {
+    /// Create empty statistics for the given [Layout]
+    pub fn new(layout: Layout) -> KeyStats {
+        KeyStats {
+            layout,
+            keys: BTreeMap::new(),
+            bigrams: BTreeMap::new(),
+            trigrams: BTreeMap::new(),
+            same_finger: Interval::default(),
+            same_hand: Interval::default(),
+            alternation: Interval::default(),
+            keypad: BTreeMap::new(),
+            on_keypad: false,
+            last: None,
+            recent: Vec::new(),
+            recorded: None,
+        }
+    }
+
+    /// Record a typed character.
+    ///
+    /// The `expected` grapheme is the grapheme of the prompt at the position of the cursor,
+    /// it is [None] when the user typed past the end of the prompt.
+    pub fn record(&mut self, expected: Option<&str>, correct: bool) {
+        let now = Instant::now();
+        let interval = self.last.map(|last| now.duration_since(last));
+        self.recorded = None;
+
+        if let Some(expected) = expected {
+            let mut recorded = vec![expected.to_string()];
+            // The keys of the numeric keypad are not typed by the fingers of the layout
+            let keys = match self.on_keypad {
+                true => &mut self.keypad,
+                false => &mut self.keys,
+            };
+            keys.entry(expected.to_string())
+                .or_default()
+                .add(correct, interval);
+
+            if let Some((previous, time)) = self.recent.last() {
+                let interval = now.duration_since(*time);
+                let bigram = format!("{}{}", previous, expected);
+                self.bigrams
+                    .entry(bigram.clone())
+                    .or_default()
+                    .add(correct, Some(interval));
+                recorded.push(bigram);
+                if correct {
+                    let previous = previous.clone();
+                    self.record_transition(&previous, expected, interval);
+                }
+            }
+            if let [(first, time), (second, _)] = &self.recent[..] {
+                let trigram = format!("{}{}{}", first, second, expected);
+                self.trigrams
+                    .entry(trigram.clone())
+                    .or_default()
+                    .add(correct, Some(now.duration_since(*time)));
+                recorded.push(trigram);
+            }
+            self.recorded = Some(Recorded {
+                keys: recorded,
+                keypad: self.on_keypad,
+                correct,
+            });
+        }
+
+        self.last = Some(now);
+        match (correct, expected) {
+            (true, Some(expected)) => {
+                self.recent.push((expected.to_string(), now));
+                if self.recent.len() > 2 {
+                    self.recent.remove(0);
+                }
+            }
+            _ => self.recent.clear(),
+        }
+    }
+
+    /// Change whether the last recorded character was correct,
+    /// when a combining character completed the grapheme after it was recorded.
+    pub fn amend(&mut self, correct: bool) {
+        let Some(recorded) = &mut self.recorded else {
+            return;
+        };
+        if recorded.correct == correct {
+            return;
+        }
+        recorded.correct = correct;
+
+        let keys = match recorded.keypad {
+            true => &mut self.keypad,
+            false => &mut self.keys,
+        };
+        let records = [keys, &mut self.bigrams, &mut self.trigrams];
+        for (map, key) in records.into_iter().zip(recorded.keys.iter()) {
+            if let Some(record) = map.get_mut(key) {
+                record.amend(correct);
+            }
+        }
+        if !correct {
+            self.recent.clear();
+        }
+    }
+
+    /// Record a keystroke that does not type a character, like a backspace or an enter.
+    pub fn press(&mut self) {
+        self.last = Some(Instant::now());
+        self.recent.clear();
+        self.recorded = None;
+    }
+
+    /// Forget the previous keystroke, so the time while the exercise was interrupted is not measured.
+    pub fn interrupt(&mut self) {
+        self.last = None;
+        self.recent.clear();
+    }
+
+    /// Classify the transition between two consecutive correct keys by the fingers that typed them.
+    fn record_transition(&mut self, from: &str, to: &str, interval: Duration) {
+        let (Some(from_finger), Some(to_finger)) = (self.finger(from), self.finger(to))
+        else {
+            return;
+        };
+
+        if from_finger == to_finger {
+            // Repeating the same key is not a same finger bigram
+            if from != to && from_finger != Finger::Thumb {
+                self.same_finger.add(interval);
+            }
+        } else if let (Some(from_hand), Some(to_hand)) = (from_finger.hand(), to_finger.hand()) {
+            match from_hand == to_hand {
+                true => self.same_hand.add(interval),
+                false => self.alternation.add(interval),
+            }
+        }
+    }
+
+    /// Returns the [Finger] of the [Layout] that types the grapheme, if it is a single character on the layout.
+    fn finger(&self, grapheme: &str) -> Option<Finger> {
+        let mut chars = grapheme.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => self.layout.finger(c),
+            _ => None,
+        }
+    }
+
+    /// Aggregate the statistics of the keys by the [Finger] that types them.
+    pub fn fingers(&self) -> BTreeMap<Finger, KeyRecord> {
+        let mut fingers: BTreeMap<Finger, KeyRecord> = BTreeMap::new();
+        for (key, record) in &self.keys {
+            if let Some(finger) = self.finger(key) {
+                fingers.entry(finger).or_default().merge(record);
+            }
+        }
+        fingers
+    }
+
+    /// Aggregate the statistics of all the keys that were typed on the numeric keypad.
+    pub fn keypad_total(&self) -> KeyRecord {
+        let mut total = KeyRecord::default();
+        for record in self.keypad.values() {
+            total.merge(record);
+        }
+        total
+    }
+
+    /// Aggregate the statistics of the keys by the [Hand] that types them.
+    pub fn hands(&self) -> BTreeMap<Hand, KeyRecord> {
+        let mut hands: BTreeMap<Hand, KeyRecord> = BTreeMap::new();
+        for (finger, record) in &self.fingers() {
+            if let Some(hand) = finger.hand() {
+                hands.entry(hand).or_default().merge(record);
+            }
+        }
+        hands
+    }
+
+    /// Returns the finger with the highest ratio of mistakes, this is the weak link of the typist.
+    pub fn weakest_finger(&self) -> Option<(Finger, KeyRecord)> {
+        self.fingers()
+            .into_iter()
+            .filter(|(_, record)| record.count_fault > 0)
+            .max_by(|(_, a), (_, b)| {
+                let ratio =
+                    |r: &KeyRecord| r.count_fault as f64 / (r.count_correct + r.count_fault) as f64;
+                ratio(a).total_cmp(&ratio(b))
+            })
+    }
+}
+
+/// Serialize the statistics of the keys, together with the aggregation by finger and hand.
+impl Serialize for KeyStats {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("KeyStats", 9)?;
+        state.serialize_field("keys", &self.keys)?;
+        state.serialize_field("bigrams", &self.bigrams)?;
+        state.serialize_field("trigrams", &self.trigrams)?;
+        state.serialize_field("fingers", &self.fingers())?;
+        state.serialize_field("hands", &self.hands())?;
+        state.serialize_field("same_finger", &self.same_finger)?;
+        state.serialize_field("same_hand", &self.same_hand)?;
+        state.serialize_field("alternation", &self.alternation)?;
+        state.serialize_field("keypad", &self.keypad)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn amend_a_key_of_the_keypad() {
+        let mut stats = KeyStats::new(Layout::default());
+        stats.record(Some("5"), false);
+        stats.on_keypad = true;
+        stats.record(Some("5"), true);
+        stats.amend(false);
+
+        let keypad = stats.keypad["5"];
+        assert_eq!((keypad.count_correct, keypad.count_fault), (0, 1));
+        // The same key on the main keyboard is left as it was
+        let key = stats.keys["5"];
+        assert_eq!((key.count_correct, key.count_fault), (0, 1));
+    }
+
+    #[test]
+    fn amend_does_not_lower_a_counter_below_0() {
+        let mut record = KeyRecord::default();
+        record.amend(true);
+        assert_eq!((record.count_correct, record.count_fault), (0, 0));
+
+        record.add(false, None);
+        record.amend(true);
+        assert_eq!((record.count_correct, record.count_fault), (1, 0));
+    }
+}
@@ -0,0 +1,181 @@
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+
+/// The hands that are used to type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hand::Left => write!(f, "Left"),
+            Hand::Right => write!(f, "Right"),
+        }
+    }
+}
+
+impl Serialize for Hand {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// The fingers that are assigned to the keys of a [Layout].
+///
+/// The thumbs are combined in a single finger because the space bar can be pressed with either hand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Finger {
+    LeftPinky,
+    LeftRing,
+    LeftMiddle,
+    LeftIndex,
+    Thumb,
+    RightIndex,
+    RightMiddle,
+    RightRing,
+    RightPinky,
+}
+
+impl Finger {
+    /// Returns the hand of the finger, the [Finger::Thumb] does not belong to a single hand.
+    pub fn hand(&self) -> Option<Hand> {
+        match self {
+            Finger::LeftPinky | Finger::LeftRing | Finger::LeftMiddle | Finger::LeftIndex => {
+                Some(Hand::Left)
+            }
+            Finger::Thumb => None,
+            Finger::RightIndex | Finger::RightMiddle | Finger::RightRing | Finger::RightPinky => {
+                Some(Hand::Right)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Finger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Finger::LeftPinky => write!(f, "Left pinky"),
+            Finger::LeftRing => write!(f, "Left ring"),
+            Finger::LeftMiddle => write!(f, "Left middle"),
+            Finger::LeftIndex => write!(f, "Left index"),
+            Finger::Thumb => write!(f, "Thumb"),
+            Finger::RightIndex => write!(f, "Right index"),
+            Finger::RightMiddle => write!(f, "Right middle"),
+            Finger::RightRing => write!(f, "Right ring"),
+            Finger::RightPinky => write!(f, "Right pinky"),
+        }
+    }
+}
+
+impl Serialize for Finger {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+use Finger::*;
+
+/// The fingers that are used for the keys of each row of the keyboard, from left to right.
+///
+/// The assignment follows the standard touch typing method on a row-staggered keyboard,
+/// so it is the same for every [Layout]. Only the characters on the keys change.
+#[rustfmt::skip]
+const FINGERS: [&[Finger]; 4] = [
+    &[LeftPinky, LeftPinky, LeftRing, LeftMiddle, LeftIndex, LeftIndex, RightIndex, RightIndex, RightMiddle, RightRing, RightPinky, RightPinky, RightPinky],
+    &[LeftPinky, LeftRing, LeftMiddle, LeftIndex, LeftIndex, RightIndex, RightIndex, RightMiddle, RightRing, RightPinky, RightPinky, RightPinky, RightPinky],
+    &[LeftPinky, LeftRing, LeftMiddle, LeftIndex, LeftIndex, RightIndex, RightIndex, RightMiddle, RightRing, RightPinky, RightPinky],
+    &[LeftPinky, LeftRing, LeftMiddle, LeftIndex, LeftIndex, RightIndex, RightIndex, RightMiddle, RightRing, RightPinky],
+];
+
+/// A keyboard layout that assigns a [Finger] to every character that can be typed.
+///
+/// Each row contains the characters of the keys without and with shift.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub name: &'static str,
+    rows: [(&'static str, &'static str); 4],
+}
+
+impl Layout {
+    pub const QWERTY: Layout = Layout {
+        name: "qwerty",
+        rows: [
+            ("`1234567890-=", "~!@#$%^&*()_+"),
+            ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+            ("asdfghjkl;'", "ASDFGHJKL:\""),
+            ("zxcvbnm,./", "ZXCVBNM<>?"),
+        ],
+    };
+
+    pub const DVORAK: Layout = Layout {
+        name: "dvorak",
+        rows: [
+            ("`1234567890[]", "~!@#$%^&*(){}"),
+            ("',.pyfgcrl/=\\", "\"<>PYFGCRL?+|"),
+            ("aoeuidhtns-", "AOEUIDHTNS_"),
+            (";qjkxbmwvz", ":QJKXBMWVZ"),
+        ],
+    };
+
+    pub const COLEMAK: Layout = Layout {
+        name: "colemak",
+        rows: [
+            ("`1234567890-=", "~!@#$%^&*()_+"),
+            ("qwfpgjluy;[]\\", "QWFPGJLUY:{}|"),
+            ("arstdhneio'", "ARSTDHNEIO\""),
+            ("zxcvbkm,./", "ZXCVBKM<>?"),
+        ],
+    };
+
+    /// All the layouts that can be selected.
+    pub const ALL: [Layout; 3] = [Layout::QWERTY, Layout::DVORAK, Layout::COLEMAK];
+
+    /// Find a layout by its name.
+    pub fn from_name(name: &str) -> Option<Layout> {
+        Layout::ALL
+            .into_iter()
+            .find(|layout| layout.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the [Finger] that is used to type the character,
+    /// or [None] if the character is not on the layout.
+    pub fn finger(&self, c: char) -> Option<Finger> {
+        if c == ' ' {
+            return Some(Finger::Thumb);
+        }
+        self.rows
+            .iter()
+            .zip(FINGERS)
+            .find_map(|((lower, upper), fingers)| {
+                lower
+                    .chars()
+                    .position(|k| k == c)
+                    .or_else(|| upper.chars().position(|k| k == c))
+                    .map(|i| fingers[i])
+            })
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::QWERTY
+    }
+}
+
+impl Serialize for Layout {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.name)
+    }
+}
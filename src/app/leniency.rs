@@ -0,0 +1,85 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+/// Returns true if the grapheme is punctuation, including the typographic quotes, dashes and the punctuation of CJK text.
+pub fn is_punctuation(grapheme: &str) -> bool {
+    !grapheme.is_empty()
+        && grapheme.chars().all(|c| {
+            c.is_ascii_punctuation()
+                || matches!(c,
+                    '\u{2010}'..='\u{2027}' | '\u{2030}'..='\u{205E}' | '\u{3001}'..='\u{3003}'
+                    | '\u{3008}'..='\u{3011}' | '«' | '»' | '¡' | '¿' | '·'
+                )
+        })
+}
+
+/// Replace the typographic quotes, dashes and spaces by their ASCII equivalents.
+fn ascii_typography(c: char) -> char {
+    match c {
+        '‘' | '’' | '‚' | '‛' | '′' | 'ʼ' => '\'',
+        '“' | '”' | '„' | '‟' | '″' | '«' | '»' => '"',
+        '\u{2010}'..='\u{2015}' | '−' => '-',
+        '\u{00A0}' | '\u{2007}' | '\u{2009}' | '\u{202F}' => ' ',
+        c => c,
+    }
+}
+
+/// The differences between the typed text and the prompt that are not counted as mistakes.
+///
+/// Every typed grapheme that is only correct because of a leniency is counted separately,
+/// so the scores of a lenient exercise can be told apart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Leniency {
+    pub case: bool,        // an uppercase letter can be typed in lowercase and the other way around
+    pub punctuation: bool, // the punctuation of the prompt can be skipped, and any punctuation matches
+    pub typography: bool,  // the typographic quotes, dashes and spaces can be typed in ASCII
+    pub accents: bool,     // a letter with an accent can be typed without the accent
+}
+
+impl Leniency {
+    /// Returns true if any leniency is enabled.
+    pub fn any(&self) -> bool {
+        self.case || self.punctuation || self.typography || self.accents
+    }
+
+    /// Returns true if the typed grapheme is accepted for the expected grapheme of the prompt.
+    pub fn matches(&self, expected: &str, typed: &str) -> bool {
+        if expected == typed {
+            return true;
+        }
+        if self.punctuation && is_punctuation(expected) && is_punctuation(typed) {
+            return true;
+        }
+        self.any() && self.fold(expected) == self.fold(typed)
+    }
+
+    /// Returns the grapheme without the differences that are allowed.
+    fn fold(&self, grapheme: &str) -> String {
+        let mut folded: String = match self.accents {
+            true => grapheme.nfd().filter(|&c| !is_combining_mark(c)).collect(),
+            false => grapheme.to_string(),
+        };
+        if self.typography {
+            folded = folded.chars().map(ascii_typography).collect();
+        }
+        if self.case {
+            folded = folded.to_lowercase();
+        }
+        folded
+    }
+}
+
+/// Serialize the leniency with a flag for every difference that is allowed.
+impl Serialize for Leniency {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Leniency", 4)?;
+        state.serialize_field("case", &self.case)?;
+        state.serialize_field("punctuation", &self.punctuation)?;
+        state.serialize_field("typography", &self.typography)?;
+        state.serialize_field("accents", &self.accents)?;
+        state.end()
+    }
+}
@@ -0,0 +1,295 @@
+use super::{
+    builtin::{Builtin, BUILTINS},
+    generator::{Generator, GENERATORS},
+};
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use std::{env, ffi::OsString, fmt, fs, path::PathBuf};
+
+/// The tabs of the [Menu].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tab {
+    Recent,
+    Files,
+    Exercises,
+    Settings,
+}
+
+impl Tab {
+    /// All the tabs, in the order they are shown.
+    pub const ALL: [Tab; 4] = [Tab::Recent, Tab::Files, Tab::Exercises, Tab::Settings];
+
+    /// Returns the position of the tab in [Tab::ALL].
+    pub fn index(&self) -> usize {
+        Tab::ALL
+            .iter()
+            .position(|tab| tab == self)
+            .expect("Every tab is in the list of all tabs")
+    }
+}
+
+impl fmt::Display for Tab {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tab::Recent => write!(f, "Recent"),
+            Tab::Files => write!(f, "Files"),
+            Tab::Exercises => write!(f, "Exercises"),
+            Tab::Settings => write!(f, "Settings"),
+        }
+    }
+}
+
+/// The value of a command line argument in the settings form.
+pub enum FieldValue {
+    Flag(bool),                 // whether the flag is given
+    Number(Option<u32>, u32),   // the value, if it is given, and the step to change it
+    Choice(Vec<String>, usize), // the possible values and the index of the selected value
+}
+
+/// A command line argument that can be changed in the settings form.
+pub struct Field {
+    pub long: String, // the long name of the argument, without the dashes
+    pub help: String,
+    pub value: FieldValue,
+}
+
+impl Field {
+    /// Toggle a flag, or select the next value of a choice or a number.
+    fn toggle(&mut self) {
+        match &mut self.value {
+            FieldValue::Flag(flag) => *flag = !*flag,
+            _ => self.increase(),
+        }
+    }
+
+    /// Select the next value.
+    fn increase(&mut self) {
+        match &mut self.value {
+            FieldValue::Flag(flag) => *flag = true,
+            FieldValue::Number(value, step) => *value = Some(value.map_or(*step, |v| v + *step)),
+            FieldValue::Choice(values, index) => *index = (*index + 1) % values.len(),
+        }
+    }
+
+    /// Select the previous value, a number below its step is turned off.
+    fn decrease(&mut self) {
+        match &mut self.value {
+            FieldValue::Flag(flag) => *flag = false,
+            FieldValue::Number(value, step) => {
+                *value = value.filter(|&v| v > *step).map(|v| v - *step)
+            }
+            FieldValue::Choice(values, index) => {
+                *index = (*index + values.len() - 1) % values.len()
+            }
+        }
+    }
+
+    /// Returns the value in the form it is shown in the settings form.
+    pub fn label(&self) -> String {
+        match &self.value {
+            FieldValue::Flag(true) => String::from("[x]"),
+            FieldValue::Flag(false) => String::from("[ ]"),
+            FieldValue::Number(Some(value), _) => value.to_string(),
+            FieldValue::Number(None, _) => String::from("off"),
+            FieldValue::Choice(values, index) => values[*index].clone(),
+        }
+    }
+
+    /// Returns the command line arguments that give the field its value.
+    fn arguments(&self) -> Vec<OsString> {
+        let long = OsString::from(format!("--{}", self.long));
+        match &self.value {
+            FieldValue::Flag(true) => vec![long],
+            FieldValue::Number(Some(value), _) => vec![long, value.to_string().into()],
+            FieldValue::Choice(values, index) => vec![long, values[*index].clone().into()],
+            _ => vec![],
+        }
+    }
+}
+
+/// An entry of the directory that is shown in the file browser.
+pub struct Entry {
+    pub name: String,
+    pub path: PathBuf,
+    pub directory: bool,
+}
+
+/// The text the user selected to train with.
+pub enum Source {
+    File(PathBuf),
+    Builtin(&'static Builtin),
+    Generator(&'static Generator),
+}
+
+/// The home screen that is shown when no file is given.
+///
+/// The user can select a recent file, browse for a new file or select a built-in exercise,
+/// after changing the settings of the exercise in the settings form.
+pub struct Menu {
+    pub tab: Tab,
+    pub recent: Vec<PathBuf>, // the files of the previous exercises, the most recent first
+    pub directory: PathBuf,   // the directory that is shown in the file browser
+    pub entries: Vec<Entry>,
+    pub fields: Vec<Field>,
+    pub message: Option<String>, // an error that is shown until the next key is pressed
+    pub selection: Option<Source>, // the text that is selected to start an exercise with
+    pub quit: bool,
+
+    cursors: [usize; 4], // the selected item of every tab
+}
+
+impl Menu {
+    /// Create the menu, the file browser starts in the current directory.
+    pub fn build(recent: Vec<PathBuf>, fields: Vec<Field>) -> Result<Menu> {
+        let mut menu = Menu {
+            tab: match recent.is_empty() {
+                true => Tab::Files,
+                false => Tab::Recent,
+            },
+            recent,
+            directory: env::current_dir()?,
+            entries: Vec::new(),
+            fields,
+            message: None,
+            selection: None,
+            quit: false,
+            cursors: [0; 4],
+        };
+        menu.read_directory();
+        Ok(menu)
+    }
+
+    /// Returns the index of the selected item of the current tab.
+    pub fn cursor(&self) -> usize {
+        self.cursors[self.tab.index()]
+    }
+
+    /// Returns the amount of items in the current tab.
+    fn len(&self) -> usize {
+        match self.tab {
+            Tab::Recent => self.recent.len(),
+            Tab::Files => self.entries.len(),
+            Tab::Exercises => BUILTINS.len() + GENERATORS.len(),
+            Tab::Settings => self.fields.len(),
+        }
+    }
+
+    /// Handles the key events of the menu.
+    ///
+    /// The tabs are switched with tab and backtab, and the items are selected with the arrows and enter.
+    /// In the settings form, the values are changed with the left and right arrows.
+    /// In the file browser, backspace goes to the parent directory.
+    pub fn update(&mut self, key_event: KeyEvent) {
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+        self.message = None;
+
+        let index = self.tab.index();
+        let cursor = self.cursors[index];
+        match key_event.code {
+            KeyCode::Char('c') | KeyCode::Char('C')
+                if key_event.modifiers == KeyModifiers::CONTROL =>
+            {
+                self.quit = true
+            }
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Tab => self.tab = Tab::ALL[(index + 1) % Tab::ALL.len()],
+            KeyCode::BackTab => self.tab = Tab::ALL[(index + Tab::ALL.len() - 1) % Tab::ALL.len()],
+            KeyCode::Up => self.cursors[index] = cursor.saturating_sub(1),
+            KeyCode::Down => {
+                self.cursors[index] = usize::min(cursor + 1, self.len().saturating_sub(1))
+            }
+            KeyCode::Enter => self.select(),
+            KeyCode::Char(' ') if self.tab == Tab::Settings => self.fields[cursor].toggle(),
+            KeyCode::Left if self.tab == Tab::Settings => self.fields[cursor].decrease(),
+            KeyCode::Right if self.tab == Tab::Settings => self.fields[cursor].increase(),
+            KeyCode::Backspace if self.tab == Tab::Files => {
+                if let Some(parent) = self.directory.parent() {
+                    self.open(parent.to_path_buf());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Select the item under the cursor: start an exercise, open a directory or change a setting.
+    fn select(&mut self) {
+        let cursor = self.cursor();
+        match self.tab {
+            Tab::Recent => self.selection = self.recent.get(cursor).cloned().map(Source::File),
+            Tab::Files => match self.entries.get(cursor) {
+                Some(entry) if entry.directory => self.open(entry.path.clone()),
+                Some(entry) => self.selection = Some(Source::File(entry.path.clone())),
+                None => {}
+            },
+            Tab::Exercises => {
+                self.selection = match cursor.checked_sub(BUILTINS.len()) {
+                    Some(index) => GENERATORS.get(index).map(Source::Generator),
+                    None => BUILTINS.get(cursor).map(Source::Builtin),
+                }
+            }
+            Tab::Settings => self.fields[cursor].toggle(),
+        }
+    }
+
+    /// Show the given directory in the file browser.
+    fn open(&mut self, directory: PathBuf) {
+        self.directory = directory;
+        self.cursors[Tab::Files.index()] = 0;
+        self.read_directory();
+    }
+
+    /// Read the entries of the current directory, the directories first and the hidden entries are skipped.
+    fn read_directory(&mut self) {
+        self.entries.clear();
+        if let Some(parent) = self.directory.parent() {
+            self.entries.push(Entry {
+                name: String::from(".."),
+                path: parent.to_path_buf(),
+                directory: true,
+            });
+        }
+
+        let read = match fs::read_dir(&self.directory) {
+            Ok(read) => read,
+            Err(e) => {
+                self.message = Some(format!(
+                    "Could not read {}: {}",
+                    self.directory.display(),
+                    e
+                ));
+                return;
+            }
+        };
+        let mut entries: Vec<Entry> = read
+            .filter_map(|entry| entry.ok())
+            .map(|entry| Entry {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                directory: entry.path().is_dir(),
+                path: entry.path(),
+            })
+            .filter(|entry| !entry.name.starts_with('.'))
+            .collect();
+        entries.sort_by(|a, b| {
+            b.directory
+                .cmp(&a.directory)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        self.entries.extend(entries);
+    }
+
+    /// Returns the command line arguments of an exercise of the given source and the values of the settings form.
+    pub fn arguments(&self, source: &Source) -> Vec<OsString> {
+        let mut arguments = vec![OsString::from(env!("CARGO_PKG_NAME"))];
+        arguments.extend(self.fields.iter().flat_map(|field| field.arguments()));
+        match source {
+            Source::File(path) => arguments.extend([OsString::from("--"), path.into()]),
+            Source::Builtin(builtin) => arguments.extend(["--", builtin.name].map(OsString::from)),
+            Source::Generator(generator) => {
+                arguments.extend(["--generate", generator.name].map(OsString::from))
+            }
+        }
+        arguments
+    }
+}
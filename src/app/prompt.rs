@@ -1,105 +1,636 @@
+use super::{
+    keys::KeyStats,
+    layout::Layout,
+    leniency::{is_punctuation, Leniency},
+    settings::Stop,
+};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::time::Duration;
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Split the text in grapheme clusters after the normalization to NFC.
+///
+/// A character with an accent is a single grapheme, whether it was written with a combining mark or not,
+/// and an emoji with a modifier is a single grapheme as well.
+pub fn graphemes(text: &str) -> Vec<String> {
+    let text: String = text.nfc().collect();
+    text.graphemes(true).map(String::from).collect()
+}
+
+/// Returns true if the grapheme is whitespace.
+pub fn is_whitespace(grapheme: &str) -> bool {
+    grapheme.chars().all(char::is_whitespace)
+}
+
+/// Returns the combining accent of a dead key, the spacing accent that a terminal sends when the key is not composed.
+fn dead_key(c: char) -> Option<char> {
+    match c {
+        '`' => Some('\u{0300}'),
+        '´' | '\'' => Some('\u{0301}'),
+        '^' => Some('\u{0302}'),
+        '~' => Some('\u{0303}'),
+        '¯' => Some('\u{0304}'),
+        '˘' => Some('\u{0306}'),
+        '˙' => Some('\u{0307}'),
+        '¨' | '"' => Some('\u{0308}'),
+        '˚' | '°' => Some('\u{030A}'),
+        '˝' => Some('\u{030B}'),
+        'ˇ' => Some('\u{030C}'),
+        '¸' => Some('\u{0327}'),
+        '˛' => Some('\u{0328}'),
+        _ => None,
+    }
+}
 
 /// Maintains the current prompt the user is typing and counts the button presses.
+///
+/// The prompt and the typed text are compared per grapheme cluster, see [graphemes].
 pub struct Prompt {
-    pub prompt: Vec<char>,
-    pub typed: Vec<char>,
+    pub prompt: Vec<String>,
+    pub typed: Vec<String>,
     pub count_correct: usize, // the characters do not need to be submitted for them to count
     pub count_correct_remove: usize,
     pub count_fault: usize,
     pub count_fault_remove: usize,
+    pub count_lenient: usize, // the characters that are only correct because of the leniency, or that were skipped
     pub failed: bool, // if one character was wrong in the prompt, it is counted as failed
+    pub count_words: usize, // the words that the cursor passed in the finished prompts
+    pub count_chars: usize, // the characters that the cursor passed in the finished prompts
+    pub keys: KeyStats, // the statistics of the individual keys
+    pub stop: Option<Stop>, // the mode that stops the cursor on a mistake
+    pub rejected: Option<String>, // the last character that was refused by the stop mode
+    pub composing: String, // the characters of a dead key or an input method that are not committed yet
+    pub leniency: Leniency, // the differences with the prompt that are not mistakes
+
+    skipped: Vec<usize>, // the positions of the punctuation that was skipped, see [Leniency::punctuation]
 }
 
 impl Prompt {
     /// Create a new prompt
-    pub fn new(prompt: Vec<char>) -> Prompt {
+    pub fn new(
+        prompt: Vec<String>,
+        layout: Layout,
+        stop: Option<Stop>,
+        leniency: Leniency,
+    ) -> Prompt {
         Prompt {
-            prompt: prompt,
+            prompt,
             typed: Vec::new(),
             count_correct: 0,
             count_correct_remove: 0,
             count_fault: 0,
             count_fault_remove: 0,
+            count_lenient: 0,
             failed: false,
+            count_words: 0,
+            count_chars: 0,
+            keys: KeyStats::new(layout),
+            stop,
+            rejected: None,
+            composing: String::new(),
+            leniency,
+            skipped: Vec::new(),
         }
     }
 
     /// Finish a prompt.
     ///
-    /// Any typed characters that are missing compared to the prompt are counted as mistakes.
+    /// Any typed characters that are missing compared to the prompt are counted as mistakes,
+    /// except for the punctuation that can be skipped.
     pub fn finish(&mut self) {
-        self.count_fault += self.count_missing();
+        self.commit();
+        let missing = self.count_missing();
+        self.count_lenient += self.prompt.len().saturating_sub(self.typed.len()) - missing;
+        self.count_fault += missing;
+        self.count_words += self.passed_words();
+        self.count_chars += self.passed_chars();
+        self.keys.press();
         self.prompt = Vec::new();
         self.typed = Vec::new();
+        self.rejected = None;
+        self.skipped.clear();
     }
 
     /// Change the current prompt.
     ///
     /// This function should only be used after [Prompt::finish()] is called.
-    pub fn set(&mut self, prompt: Vec<char>) {
+    pub fn set(&mut self, prompt: Vec<String>) {
         self.prompt = prompt;
         self.failed = false;
     }
 
     /// Count the missing characters of a prompt
     pub fn count_missing(&self) -> usize {
-        if self.prompt.len() > self.typed.len() {
-            self.prompt.len() - self.typed.len()
-        } else {
-            0
+        self.prompt
+            .iter()
+            .skip(self.typed.len())
+            .filter(|g| !self.skippable(g))
+            .count()
+    }
+
+    /// Returns true if the grapheme of the prompt can be skipped, see [Leniency::punctuation].
+    fn skippable(&self, grapheme: &str) -> bool {
+        self.leniency.punctuation && is_punctuation(grapheme)
+    }
+
+    /// Returns true if every character of the prompt is typed, the punctuation that can be skipped is not needed.
+    pub fn is_complete(&self) -> bool {
+        self.typed.len() <= self.prompt.len()
+            && self.prompt[self.typed.len()..]
+                .iter()
+                .all(|g| self.skippable(g))
+    }
+
+    /// Returns true if the typed grapheme at the index is accepted for the grapheme of the prompt.
+    pub fn correct_at(&self, index: usize) -> bool {
+        match (self.prompt.get(index), self.typed.get(index)) {
+            (Some(expected), Some(typed)) => self.leniency.matches(expected, typed),
+            _ => false,
         }
     }
 
-    /// Check if the currently typed text is exactly the same as the prompt
+    /// Count the characters of the prompt that the cursor has passed
+    fn passed_chars(&self) -> usize {
+        usize::min(self.typed.len(), self.prompt.len())
+    }
+
+    /// Count the words of the prompt that the cursor has passed, a word is passed when its last character is typed
+    fn passed_words(&self) -> usize {
+        (0..self.passed_chars())
+            .filter(|&i| {
+                !is_whitespace(&self.prompt[i])
+                    && self.prompt.get(i + 1).map_or(true, |g| is_whitespace(g))
+            })
+            .count()
+    }
+
+    /// Returns the amount of words that the cursor has passed, in all the prompts of the exercise
+    pub fn words(&self) -> usize {
+        self.count_words + self.passed_words()
+    }
+
+    /// Returns the amount of characters that the cursor has passed, in all the prompts of the exercise
+    pub fn chars(&self) -> usize {
+        self.count_chars + self.passed_chars()
+    }
+
+    /// Check if the currently typed text is the same as the prompt, with the differences the leniency allows
     pub fn is_correct(&self) -> bool {
-        self.typed == self.prompt
+        self.is_complete() && (0..self.typed.len()).all(|i| self.correct_at(i))
     }
 
     /// Returns the ratio of the correctly typed characters compared to the total amount of typed characters.
     pub fn ratio(&self) -> f64 {
         match self.count_correct + self.count_fault {
-            0 => 1 as f64,
+            0 => 1.0,
             total => self.count_correct as f64 / total as f64,
         }
     }
 
-    /// Push a typed character, check if the character was correct, and update the counters accordingly.
+    /// Returns true if the word at the cursor contains a mistake.
+    pub fn word_failed(&self) -> bool {
+        let start = self
+            .typed
+            .iter()
+            .rposition(|g| is_whitespace(g))
+            .map_or(0, |i| i + 1);
+        (start..self.typed.len()).any(|i| !self.correct_at(i))
+    }
+
+    /// Handle a typed character, it is committed when it does not start a composition.
+    ///
+    /// The characters are composed, and not counted yet, while they form the start of the expected grapheme,
+    /// like a part of an emoji or a syllable from an input method.
+    /// A dead key is composed with the next character when the expected grapheme has its accent,
+    /// in case the terminal sends the dead key and the letter separately.
+    /// A dead key followed by a space is the accent itself.
     pub fn type_char(&mut self, c: char) {
+        let candidate = format!("{}{}", self.composing, c);
+        let Some(expected) = self.next_expected(&candidate) else {
+            self.commit();
+            self.commit_char(c);
+            return;
+        };
+
+        let composed: Vec<char> = candidate.nfd().collect();
+        let target: Vec<char> = expected.nfd().collect();
+        let accent = dead_key(c).filter(|accent| target.contains(accent));
+
+        let grapheme: String = candidate.nfc().collect();
+        if self.leniency.matches(expected, &grapheme) {
+            self.composing.clear();
+            self.type_grapheme(grapheme);
+        } else if composed.len() < target.len() && target.starts_with(&composed) {
+            self.composing = candidate;
+        } else if let Some(accent) = self.composing.chars().next().and_then(dead_key) {
+            let dead = std::mem::take(&mut self.composing);
+            match c {
+                ' ' => self.type_grapheme(dead),
+                _ => {
+                    let letter: String = [c, accent].into_iter().nfc().collect();
+                    match letter.chars().count() {
+                        1 => self.type_grapheme(letter),
+                        _ => candidate.chars().for_each(|c| self.commit_char(c)),
+                    }
+                }
+            }
+        } else if accent.is_some() {
+            self.commit();
+            self.composing.push(c);
+        } else {
+            self.commit();
+            self.commit_char(c);
+        }
+    }
+
+    /// Returns the grapheme of the prompt that the typed grapheme is compared with,
+    /// after the punctuation that would be skipped.
+    fn next_expected(&self, grapheme: &str) -> Option<&String> {
+        self.prompt[usize::min(self.typed.len(), self.prompt.len())..]
+            .iter()
+            .find(|expected| !self.skippable(expected) || self.leniency.matches(expected, grapheme))
+    }
+
+    /// Commit the characters that are being composed, they are counted as they were typed.
+    pub fn commit(&mut self) {
+        let composing = std::mem::take(&mut self.composing);
+        composing.chars().for_each(|c| self.commit_char(c));
+    }
+
+    /// Push a committed character, check if the character was correct, and update the counters accordingly.
+    ///
+    /// A combining character that belongs to the previously typed grapheme, like a separate accent,
+    /// completes that grapheme instead, which is then checked again.
+    fn commit_char(&mut self, c: char) {
+        let skipped = self.skipped.last() == Some(&self.typed.len().wrapping_sub(1));
+        if self.rejected.is_none() && !skipped {
+            if let Some(last) = self.typed.last() {
+                let combined: String = last.chars().chain([c]).nfc().collect();
+                if combined.graphemes(true).count() == 1 {
+                    self.amend(combined);
+                    return;
+                }
+            }
+        }
+        self.type_grapheme(c.to_string().nfc().collect());
+    }
+
+    /// Push a typed grapheme, check if it was correct, and update the counters accordingly.
+    ///
+    /// With a [Stop] mode, a grapheme can be refused, the cursor then does not advance:
+    /// - [Stop::Error] refuses every wrong grapheme
+    /// - [Stop::Word] refuses the whitespace that leaves a word with a mistake in it
+    ///
+    /// The punctuation that can be skipped is passed when the grapheme is not punctuation itself.
+    fn type_grapheme(&mut self, grapheme: String) {
+        while let Some(expected) = self.prompt.get(self.typed.len()) {
+            if !self.skippable(expected) || self.leniency.matches(expected, &grapheme) {
+                break;
+            }
+            self.skipped.push(self.typed.len());
+            self.typed.push(expected.clone());
+            self.count_lenient += 1;
+        }
+
         let index = self.typed.len();
-        self.typed.push(c);
+        let expected = self.prompt.get(index).map(|g| g.as_str());
+        let correct = expected.is_some_and(|e| self.leniency.matches(e, &grapheme));
+        let whitespace = is_whitespace(&grapheme);
 
-        if index < self.prompt.len() && c == self.prompt[index] {
+        // The mistake in the word is already counted
+        if self.stop == Some(Stop::Word) && correct && whitespace && self.word_failed() {
+            self.rejected = Some(grapheme);
+            return;
+        }
+
+        if correct {
             self.count_correct += 1;
+            if expected != Some(&grapheme) {
+                self.count_lenient += 1;
+            }
         } else {
             self.count_fault += 1;
             self.failed = true;
         }
+        self.keys.record(expected, correct);
+
+        let refused = match self.stop {
+            Some(Stop::Error) => !correct,
+            Some(Stop::Word) => !correct && whitespace,
+            None => false,
+        };
+        match refused {
+            true => self.rejected = Some(grapheme),
+            false => {
+                self.typed.push(grapheme);
+                self.rejected = None;
+            }
+        }
+    }
+
+    /// Replace the last typed grapheme by the completed grapheme, and correct the counters of the last grapheme.
+    fn amend(&mut self, grapheme: String) {
+        let index = self.typed.len() - 1;
+        let expected = self.prompt.get(index);
+        let was_correct = self.correct_at(index);
+        let correct = expected.is_some_and(|e| self.leniency.matches(e, &grapheme));
+        let was_lenient = was_correct && expected != self.typed.last();
+        let lenient = correct && expected != Some(&grapheme);
+
+        self.count_lenient = self.count_lenient + lenient as usize - was_lenient as usize;
+        match (was_correct, correct) {
+            (false, true) => {
+                self.count_fault -= 1;
+                self.count_correct += 1;
+            }
+            (true, false) => {
+                self.count_correct -= 1;
+                self.count_fault += 1;
+                self.failed = true;
+            }
+            _ => {}
+        }
+        self.keys.amend(correct);
+        self.typed[index] = grapheme;
+    }
+
+    /// Returns true if the cursor is in a word, the last typed character is not whitespace.
+    pub fn in_word(&self) -> bool {
+        self.typed.last().is_some_and(|g| !is_whitespace(g))
+    }
+
+    /// Remove the last word from the typed text, every removed character updates the counters.
+    ///
+    /// Like the delete-word of a shell, the whitespace after the word is removed first,
+    /// unless the removal is confined to the current word.
+    pub fn remove_word(&mut self, confined: bool) {
+        self.composing.clear();
+        while !confined && self.typed.last().is_some_and(|g| is_whitespace(g)) {
+            self.remove_char();
+        }
+        while self.in_word() {
+            self.remove_char();
+        }
     }
 
     /// Remove a character from the prompt and update the counter
+    ///
+    /// The punctuation that was skipped after the character is removed with it.
+    /// The characters that are being composed are discarded first, they were not counted.
     pub fn remove_char(&mut self) {
-        if let Some(c) = self.typed.pop() {
-            if self.typed.len() >= self.prompt.len() || c != self.prompt[self.typed.len()] {
-                self.count_fault_remove += 1;
-            } else {
-                self.count_correct_remove += 1;
+        self.keys.press();
+        self.rejected = None;
+        if !self.composing.is_empty() {
+            self.composing.clear();
+            return;
+        }
+        while !self.typed.is_empty() && self.skipped.last() == Some(&(self.typed.len() - 1)) {
+            self.skipped.pop();
+            self.typed.pop();
+        }
+        if !self.typed.is_empty() {
+            match self.correct_at(self.typed.len() - 1) {
+                true => self.count_correct_remove += 1,
+                false => self.count_fault_remove += 1,
             }
+            self.typed.pop();
         }
     }
 }
 
-/// Serialize a prompt, only the counters are relevant.
+/// Serialize a prompt, only the counters and the statistics of the keys are relevant.
 impl Serialize for Prompt {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Prompt", 4)?;
+        let mut state = serializer.serialize_struct("Prompt", 6)?;
         state.serialize_field("count_correct", &self.count_correct)?;
         state.serialize_field("count_correct_remove", &self.count_correct_remove)?;
         state.serialize_field("count_fault", &self.count_fault)?;
         state.serialize_field("count_fault_remove", &self.count_fault_remove)?;
+        state.serialize_field("count_lenient", &self.count_lenient)?;
+        state.serialize_field("keys", &self.keys)?;
         state.end()
     }
 }
+
+/// The result of a single finished prompt of an exercise.
+#[derive(Clone, Copy, Debug)]
+pub struct PromptResult {
+    pub chars: usize, // the length of the prompt in graphemes
+    pub correct: usize,
+    pub mistakes: usize,
+    pub time: Duration, // the time between the end of the previous prompt and the end of this one
+}
+
+/// Serialize the result of a prompt, the time in milliseconds.
+impl Serialize for PromptResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("PromptResult", 4)?;
+        state.serialize_field("chars", &self.chars)?;
+        state.serialize_field("correct", &self.correct)?;
+        state.serialize_field("mistakes", &self.mistakes)?;
+        state.serialize_field("time_ms", &(self.time.as_millis() as u64))?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prompt(text: &str, stop: Option<Stop>, leniency: Leniency) -> Prompt {
+        Prompt::new(graphemes(text), Layout::default(), stop, leniency)
+    }
+
+    fn type_text(prompt: &mut Prompt, text: &str) {
+        text.chars().for_each(|c| prompt.type_char(c));
+    }
+
+    fn typed(prompt: &Prompt) -> String {
+        prompt.typed.concat()
+    }
+
+    #[test]
+    fn nfd_input_matches_an_nfc_prompt() {
+        let mut prompt = prompt("café", None, Leniency::default());
+        type_text(&mut prompt, "cafe\u{0301}");
+        assert_eq!(prompt.typed, ["c", "a", "f", "é"]);
+        assert_eq!((prompt.count_correct, prompt.count_fault), (4, 0));
+        assert!(prompt.is_correct());
+    }
+
+    #[test]
+    fn nfc_input_matches_an_nfd_prompt() {
+        let mut prompt = prompt("cafe\u{0301}", None, Leniency::default());
+        assert_eq!(prompt.prompt.len(), 4);
+        type_text(&mut prompt, "café");
+        assert_eq!((prompt.count_correct, prompt.count_fault), (4, 0));
+        assert!(prompt.is_correct());
+    }
+
+    #[test]
+    fn emoji_with_a_modifier_is_one_grapheme() {
+        let mut prompt = prompt("a👍🏽", None, Leniency::default());
+        type_text(&mut prompt, "a👍🏽");
+        assert_eq!(prompt.typed.len(), 2);
+        assert_eq!((prompt.count_correct, prompt.count_fault), (2, 0));
+    }
+
+    #[test]
+    fn dead_key_is_composed_with_the_next_letter() {
+        let mut prompt = prompt("tête", None, Leniency::default());
+        type_text(&mut prompt, "t^");
+        assert_eq!(prompt.composing, "^");
+        assert_eq!(prompt.count_fault, 0);
+
+        type_text(&mut prompt, "ete");
+        assert_eq!(typed(&prompt), "tête");
+        assert_eq!((prompt.count_correct, prompt.count_fault), (4, 0));
+    }
+
+    #[test]
+    fn dead_key_that_does_not_compose_is_typed() {
+        let mut prompt = prompt("êa", None, Leniency::default());
+        type_text(&mut prompt, "^x");
+        assert_eq!(typed(&prompt), "^x");
+        assert_eq!((prompt.count_correct, prompt.count_fault), (0, 2));
+    }
+
+    #[test]
+    fn composition_is_discarded_by_a_backspace() {
+        let mut prompt = prompt("ê", None, Leniency::default());
+        type_text(&mut prompt, "^");
+        prompt.remove_char();
+        assert!(prompt.composing.is_empty());
+        assert!(prompt.typed.is_empty());
+        assert_eq!(prompt.count_fault, 0);
+    }
+
+    #[test]
+    fn lenient_punctuation_can_be_skipped() {
+        let leniency = Leniency {
+            punctuation: true,
+            ..Leniency::default()
+        };
+        let mut prompt = prompt("Hi, there!", None, leniency);
+        type_text(&mut prompt, "Hi there");
+        assert!(prompt.is_complete());
+        assert!(prompt.is_correct());
+        prompt.finish();
+        assert_eq!((prompt.count_correct, prompt.count_fault), (8, 0));
+        assert_eq!(prompt.count_lenient, 2);
+    }
+
+    #[test]
+    fn lenient_punctuation_matches_other_punctuation() {
+        let leniency = Leniency {
+            punctuation: true,
+            ..Leniency::default()
+        };
+        let mut prompt = prompt("a;b", None, leniency);
+        type_text(&mut prompt, "a.b");
+        assert_eq!((prompt.count_correct, prompt.count_fault), (3, 0));
+        assert_eq!(prompt.count_lenient, 1);
+    }
+
+    #[test]
+    fn ascii_quotes_and_dashes() {
+        let text = "“Hi” — it’s";
+        let typed = "\"Hi\" - it's";
+
+        let mut strict = prompt(text, None, Leniency::default());
+        type_text(&mut strict, typed);
+        assert_eq!(strict.count_fault, 4);
+
+        let leniency = Leniency {
+            typography: true,
+            ..Leniency::default()
+        };
+        let mut lenient = prompt(text, None, leniency);
+        type_text(&mut lenient, typed);
+        assert_eq!((lenient.count_correct, lenient.count_fault), (11, 0));
+        assert_eq!(lenient.count_lenient, 4);
+    }
+
+    #[test]
+    fn case_and_accents() {
+        let leniency = Leniency {
+            case: true,
+            accents: true,
+            ..Leniency::default()
+        };
+        let mut prompt = prompt("Été", None, leniency);
+        type_text(&mut prompt, "ete");
+        assert_eq!((prompt.count_correct, prompt.count_fault), (3, 0));
+        assert_eq!(prompt.count_lenient, 2);
+    }
+
+    #[test]
+    fn without_stop_a_mistake_is_typed() {
+        let mut prompt = prompt("ab", None, Leniency::default());
+        type_text(&mut prompt, "xb");
+        assert_eq!(typed(&prompt), "xb");
+        assert_eq!((prompt.count_correct, prompt.count_fault), (1, 1));
+        assert!(prompt.rejected.is_none());
+    }
+
+    #[test]
+    fn stop_on_error_refuses_a_mistake() {
+        let mut prompt = prompt("ab", Some(Stop::Error), Leniency::default());
+        type_text(&mut prompt, "x");
+        assert!(prompt.typed.is_empty());
+        assert_eq!(prompt.rejected.as_deref(), Some("x"));
+        assert_eq!(prompt.count_fault, 1);
+
+        type_text(&mut prompt, "ab");
+        assert_eq!(typed(&prompt), "ab");
+        assert!(prompt.rejected.is_none());
+        assert_eq!((prompt.count_correct, prompt.count_fault), (2, 1));
+    }
+
+    #[test]
+    fn stop_on_word_refuses_to_leave_a_wrong_word() {
+        let mut prompt = prompt("ab cd", Some(Stop::Word), Leniency::default());
+        type_text(&mut prompt, "ax ");
+        assert_eq!(typed(&prompt), "ax");
+        assert_eq!(prompt.rejected.as_deref(), Some(" "));
+        assert_eq!(prompt.count_fault, 1);
+
+        prompt.remove_char();
+        type_text(&mut prompt, "b c");
+        assert_eq!(typed(&prompt), "ab c");
+        assert_eq!(prompt.count_fault, 1);
+    }
+
+    #[test]
+    fn stop_on_word_refuses_a_wrong_space() {
+        let mut prompt = prompt("ab", Some(Stop::Word), Leniency::default());
+        type_text(&mut prompt, "a ");
+        assert_eq!(typed(&prompt), "a");
+        assert_eq!(prompt.count_fault, 1);
+    }
+
+    #[test]
+    fn remove_word() {
+        let mut prompt = prompt("ab cd", None, Leniency::default());
+        type_text(&mut prompt, "ab cx");
+        prompt.remove_word(false);
+        assert_eq!(typed(&prompt), "ab ");
+        assert_eq!(
+            (prompt.count_correct_remove, prompt.count_fault_remove),
+            (1, 1)
+        );
+
+        // Confined to the current word, the space before it is kept
+        prompt.remove_word(true);
+        assert_eq!(typed(&prompt), "ab ");
+        prompt.remove_word(false);
+        assert_eq!(typed(&prompt), "");
+    }
+}
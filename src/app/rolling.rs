@@ -0,0 +1,158 @@
+use super::prompt::Prompt;
+use std::{collections::VecDeque, time::Duration};
+
+/// The counters of the [Prompt] at a moment of the exercise.
+#[derive(Clone, Copy, Default)]
+struct Sample {
+    time: Duration,
+    correct: usize,
+    correct_remove: usize,
+    fault: usize,
+}
+
+impl Sample {
+    fn new(time: Duration, prompt: &Prompt) -> Sample {
+        Sample {
+            time,
+            correct: prompt.count_correct,
+            correct_remove: prompt.count_correct_remove,
+            fault: prompt.count_fault,
+        }
+    }
+}
+
+/// The statistics of the last minutes of an endless exercise.
+///
+/// The counters of the [Prompt] are sampled after every keystroke,
+/// the rolling statistics are the difference with the sample at the start of the window.
+/// The time is the time of the [Timer], so a pause is not part of the window.
+///
+/// [Timer]: crate::app::timer::Timer
+pub struct Rolling {
+    pub window: Duration,
+    samples: VecDeque<Sample>, // the first sample is the last one before the start of the window
+}
+
+impl Rolling {
+    /// Create the rolling statistics with the given window.
+    pub fn new(window: Duration) -> Rolling {
+        Rolling {
+            window,
+            samples: VecDeque::from([Sample::default()]),
+        }
+    }
+
+    /// Sample the counters of the prompt, the samples that are no longer needed are removed.
+    pub fn record(&mut self, time: Duration, prompt: &Prompt) {
+        self.samples.push_back(Sample::new(time, prompt));
+        while self.samples.len() > 1 && self.samples[1].time + self.window <= time {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns the last sample before the start of the window that ends at the given time.
+    ///
+    /// The samples are only removed on a keystroke, so while the user is idle the window slides past them.
+    fn start(&self, time: Duration) -> Sample {
+        self.samples
+            .iter()
+            .rev()
+            .find(|sample| sample.time + self.window <= time)
+            .or(self.samples.front())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the counters of the window, the time is the duration of the window.
+    ///
+    /// The window is shorter at the start of the exercise.
+    /// A counter can be lowered after a sample was taken, when a character is amended, so it saturates at 0.
+    fn difference(&self, time: Duration, prompt: &Prompt) -> Sample {
+        let start = self.start(time);
+        let now = Sample::new(time, prompt);
+        Sample {
+            time: Duration::min(time, self.window),
+            correct: now.correct.saturating_sub(start.correct),
+            correct_remove: now.correct_remove.saturating_sub(start.correct_remove),
+            fault: now.fault.saturating_sub(start.fault),
+        }
+    }
+
+    /// Calculate the WPM score of the window, see [Exercise::calculate_wpm].
+    ///
+    /// [Exercise::calculate_wpm]: crate::app::exercise::Exercise::calculate_wpm
+    pub fn wpm(&self, time: Duration, prompt: &Prompt) -> usize {
+        let difference = self.difference(time, prompt);
+        let letters = difference.correct.saturating_sub(difference.correct_remove);
+        let minutes = difference.time.as_secs_f32() / 60.0;
+        match minutes > 0.0 {
+            true => (letters as f32 / 5.0 / minutes).round() as usize,
+            false => 0,
+        }
+    }
+
+    /// Returns the ratio of the correctly typed characters in the window, see [Prompt::ratio].
+    pub fn ratio(&self, time: Duration, prompt: &Prompt) -> f64 {
+        let difference = self.difference(time, prompt);
+        match difference.correct + difference.fault {
+            0 => 1.0,
+            total => difference.correct as f64 / total as f64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{layout::Layout, leniency::Leniency};
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn prompt(correct: usize, fault: usize) -> Prompt {
+        let mut prompt = Prompt::new(
+            vec![String::from("a")],
+            Layout::default(),
+            None,
+            Leniency::default(),
+        );
+        prompt.count_correct = correct;
+        prompt.count_fault = fault;
+        prompt
+    }
+
+    #[test]
+    fn statistics_of_the_window() {
+        let mut rolling = Rolling::new(MINUTE);
+        rolling.record(MINUTE, &prompt(100, 0));
+        assert_eq!(rolling.wpm(MINUTE, &prompt(100, 0)), 20);
+
+        // Only the 50 characters of the last minute count
+        rolling.record(MINUTE * 2, &prompt(150, 50));
+        assert_eq!(rolling.wpm(MINUTE * 2, &prompt(150, 50)), 10);
+        assert_eq!(rolling.ratio(MINUTE * 2, &prompt(150, 50)), 0.5);
+    }
+
+    #[test]
+    fn window_slides_while_idle() {
+        let mut rolling = Rolling::new(MINUTE);
+        rolling.record(Duration::from_secs(30), &prompt(100, 10));
+        assert!(rolling.wpm(Duration::from_secs(40), &prompt(100, 10)) > 0);
+
+        // Nothing was typed in the last minute
+        assert_eq!(rolling.wpm(MINUTE * 3, &prompt(100, 10)), 0);
+        assert_eq!(rolling.ratio(MINUTE * 3, &prompt(100, 10)), 1.0);
+    }
+
+    #[test]
+    fn lowered_counters_do_not_overflow() {
+        let mut rolling = Rolling::new(MINUTE);
+        rolling.record(MINUTE * 2, &prompt(100, 10));
+        rolling.record(MINUTE * 3 + Duration::from_secs(1), &prompt(101, 10));
+
+        // An amended character lowers the counter below the start of the window
+        assert_eq!(
+            rolling.wpm(MINUTE * 3 + Duration::from_secs(2), &prompt(99, 9)),
+            0
+        );
+    }
+}
@@ -1,35 +1,221 @@
+use super::{layout::Layout, leniency::Leniency};
 use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::{fmt, time::Duration};
+
+/// The modes that stop the cursor when a mistake is made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    Error, // the cursor does not advance past a wrong character
+    Word,  // the cursor does not leave a word with a mistake in it
+}
+
+impl Stop {
+    /// The names of the modes, "off" disables them.
+    pub const NAMES: [&'static str; 3] = ["off", "error", "word"];
+
+    /// Find a mode by its name, "off" returns [None].
+    pub fn from_name(name: &str) -> Option<Stop> {
+        match name {
+            "error" => Some(Stop::Error),
+            "word" => Some(Stop::Word),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Error => write!(f, "error"),
+            Stop::Word => write!(f, "word"),
+        }
+    }
+}
+
+impl Serialize for Stop {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// An amount of text that ends the exercise when it is typed, independent of how the text is split into prompts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    Words(usize), // the words that the cursor has passed
+    Chars(usize), // the characters that the cursor has passed
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Target::Words(words) => write!(f, "{} words", words),
+            Target::Chars(chars) => write!(f, "{} characters", chars),
+        }
+    }
+}
+
+impl Serialize for Target {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// The settings that can be changed in the settings overlay of the application.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setting {
+    Highlight,
+    Blind,
+    Backspace,
+    WordBackspace,
+    Auto,
+    Correct,
+    Repeat,
+    Stop,
+    Case,
+    Punctuation,
+    Typography,
+    Accents,
+    Inline,
+    Paste,
+    Layout,
+}
+
+impl Setting {
+    /// All the settings, in the order they are shown in the overlay.
+    pub const ALL: [Setting; 15] = [
+        Setting::Highlight,
+        Setting::Blind,
+        Setting::Backspace,
+        Setting::WordBackspace,
+        Setting::Auto,
+        Setting::Correct,
+        Setting::Repeat,
+        Setting::Stop,
+        Setting::Case,
+        Setting::Punctuation,
+        Setting::Typography,
+        Setting::Accents,
+        Setting::Inline,
+        Setting::Paste,
+        Setting::Layout,
+    ];
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Setting::Highlight => write!(f, "Highlight"),
+            Setting::Blind => write!(f, "Blind"),
+            Setting::Backspace => write!(f, "Backspace"),
+            Setting::WordBackspace => write!(f, "Word only"),
+            Setting::Auto => write!(f, "Auto"),
+            Setting::Correct => write!(f, "Correct"),
+            Setting::Repeat => write!(f, "Repeat"),
+            Setting::Stop => write!(f, "Stop on"),
+            Setting::Case => write!(f, "Ignore case"),
+            Setting::Punctuation => write!(f, "Skip punct."),
+            Setting::Typography => write!(f, "ASCII quotes"),
+            Setting::Accents => write!(f, "Fold accents"),
+            Setting::Inline => write!(f, "Inline"),
+            Setting::Paste => write!(f, "Paste"),
+            Setting::Layout => write!(f, "Layout"),
+        }
+    }
+}
 
 /// Store the settings of the exercise based on the arguments that were given.
 ///
-/// These settings should not be changed during the execution of the application.
+/// These settings should not be changed while an exercise is running.
+/// The settings overlay changes a copy of the settings, that is used when the exercise is restarted.
+/// The settings that are not given can be taken from [Settings::default], where every option is off.
+#[derive(Clone, Default)]
 pub struct Settings {
     pub highlight: bool, // Highlight the correct letters in green and the mistakes in red
     pub blind: bool,     // Hide the text when the user is typing
     pub backspace: bool, // Allow the use of the backspace key
+    pub word_backspace: bool, // Only allow the backspace key within the current word
     pub auto: bool,      // Automatically progress to the next line without pressing enter
     pub correct: bool,   // Automatically progress to the next line without pressing enter
     pub repeat: bool,    // Repeat the prompt when a mistake was made while typing it
+    pub stop: Option<Stop>, // Stop the cursor on a wrong character or a wrong word
+    pub leniency: Leniency, // The differences with the prompt that are not counted as mistakes
     pub terminate: Option<usize>, // Terminate the exercise after a given amount of mistakes
+    pub target: Option<Target>, // Finish the exercise after a given amount of words or characters
+    pub layout: Layout,  // The keyboard layout that is used to assign the keys to the fingers
+    pub inline: bool,    // Type over the text of the prompt instead of in a separate box
+    pub paste: bool,     // Accept pasted text as typed text
+    pub idle: Option<Duration>, // Pause the exercise when nothing is typed during this time
+    pub endless: Option<Duration>, // The window of the rolling statistics of an endless exercise
+    pub changed: bool, // The settings were changed in the settings overlay, they differ from the arguments
 }
 impl Settings {
-    pub fn build(
-        backspace: bool,
-        highlight: bool,
-        blind: bool,
-        auto: bool,
-        correct: bool,
-        repeat: bool,
-        terminate: Option<usize>,
-    ) -> Settings {
-        Settings {
-            highlight,
-            blind,
-            backspace,
-            auto,
-            correct,
-            repeat,
-            terminate,
+    /// Change a setting: a flag is toggled and the next [Layout] is selected.
+    pub fn toggle(&mut self, setting: Setting) {
+        let flag = match setting {
+            Setting::Highlight => &mut self.highlight,
+            Setting::Blind => &mut self.blind,
+            Setting::Backspace => &mut self.backspace,
+            Setting::WordBackspace => &mut self.word_backspace,
+            Setting::Auto => &mut self.auto,
+            Setting::Correct => &mut self.correct,
+            Setting::Repeat => &mut self.repeat,
+            Setting::Case => &mut self.leniency.case,
+            Setting::Punctuation => &mut self.leniency.punctuation,
+            Setting::Typography => &mut self.leniency.typography,
+            Setting::Accents => &mut self.leniency.accents,
+            Setting::Inline => &mut self.inline,
+            Setting::Paste => &mut self.paste,
+            Setting::Stop => {
+                self.stop = match self.stop {
+                    None => Some(Stop::Error),
+                    Some(Stop::Error) => Some(Stop::Word),
+                    Some(Stop::Word) => None,
+                };
+                self.changed = true;
+                return;
+            }
+            Setting::Layout => {
+                let index = Layout::ALL
+                    .iter()
+                    .position(|&l| l == self.layout)
+                    .unwrap_or(0);
+                self.layout = Layout::ALL[(index + 1) % Layout::ALL.len()];
+                self.changed = true;
+                return;
+            }
+        };
+        *flag = !*flag;
+        self.changed = true;
+    }
+
+    /// Returns the value of a setting as it is shown in the settings overlay.
+    pub fn label(&self, setting: Setting) -> String {
+        let flag = match setting {
+            Setting::Highlight => self.highlight,
+            Setting::Blind => self.blind,
+            Setting::Backspace => self.backspace,
+            Setting::WordBackspace => self.word_backspace,
+            Setting::Auto => self.auto,
+            Setting::Correct => self.correct,
+            Setting::Repeat => self.repeat,
+            Setting::Case => self.leniency.case,
+            Setting::Punctuation => self.leniency.punctuation,
+            Setting::Typography => self.leniency.typography,
+            Setting::Accents => self.leniency.accents,
+            Setting::Inline => self.inline,
+            Setting::Paste => self.paste,
+            Setting::Stop => return self.stop.map_or(String::from("off"), |s| s.to_string()),
+            Setting::Layout => return self.layout.name.to_string(),
+        };
+        match flag {
+            true => String::from("on"),
+            false => String::from("off"),
         }
     }
 }
@@ -40,14 +226,24 @@ impl Serialize for Settings {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Settings", 3)?;
+        let mut state = serializer.serialize_struct("Settings", 17)?;
         state.serialize_field("blind", &self.blind)?;
         state.serialize_field("backspace", &self.backspace)?;
+        state.serialize_field("word_backspace", &self.word_backspace)?;
         state.serialize_field("highlight", &self.highlight)?;
         state.serialize_field("auto", &self.auto)?;
         state.serialize_field("correct", &self.correct)?;
         state.serialize_field("repeat", &self.repeat)?;
+        state.serialize_field("stop", &self.stop)?;
+        state.serialize_field("leniency", &self.leniency)?;
         state.serialize_field("terminate", &self.terminate)?;
+        state.serialize_field("target", &self.target)?;
+        state.serialize_field("layout", &self.layout)?;
+        state.serialize_field("inline", &self.inline)?;
+        state.serialize_field("paste", &self.paste)?;
+        state.serialize_field("idle", &self.idle.map(|idle| idle.as_secs()))?;
+        state.serialize_field("endless", &self.endless.map(|window| window.as_secs() / 60))?;
+        state.serialize_field("changed", &self.changed)?;
         state.end()
     }
 }
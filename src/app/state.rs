@@ -1,3 +1,4 @@
+use super::bindings::Action;
 use std::fmt;
 
 /// The possible states of the application.
@@ -23,13 +24,23 @@ impl fmt::Display for State {
 }
 
 impl State {
-    pub fn button(&self) -> &str {
+    /// All the states of the application.
+    pub const ALL: [State; 5] = [
+        State::Waiting,
+        State::Running,
+        State::Pausing,
+        State::Finished,
+        State::Quitting,
+    ];
+
+    /// Returns the actions that can be used in the state.
+    pub fn actions(&self) -> &'static [Action] {
         match self {
-            State::Waiting => "Restart: 'Ctrl+R'",
-            State::Running => "Start:   Type",
-            State::Pausing => "Pause:   'Ctrl+P'",
-            State::Finished => "Stop:    'Ctrl+C'",
-            State::Quitting => "Quit:    'Ctrl+C'",
+            State::Waiting => &[Action::Quit, Action::Settings],
+            State::Running => &[Action::Stop, Action::Restart, Action::Pause],
+            State::Pausing => &[Action::Stop, Action::Restart],
+            State::Finished => &[Action::Quit, Action::Restart, Action::Settings],
+            State::Quitting => &[],
         }
     }
 }
@@ -25,7 +25,14 @@ impl Timer {
 
     /// Stop the timer. After the timer is stopped, it can be restarted again with the [Timer::start] function.
     pub fn stop(&mut self) {
-        self.saved = self.get_time();
+        self.stop_at(Instant::now());
+    }
+
+    /// Stop the timer as if it was stopped at an earlier moment.
+    ///
+    /// This is used to exclude the time since that moment, like the time a user was idle.
+    pub fn stop_at(&mut self, moment: Instant) {
+        self.saved = self.get_time_at(moment);
         self.start = Option::None;
     }
 
@@ -36,13 +43,47 @@ impl Timer {
     }
 
     /// Get the total elapsed time
+    ///
+    /// If the optional duration is set, the elapsed time will never exceed that duration.
     pub fn get_time(&self) -> Duration {
+        self.get_time_at(Instant::now())
+    }
+
+    /// Get the total elapsed time at the given moment
+    fn get_time_at(&self, moment: Instant) -> Duration {
         let elapsed = if let Some(start) = self.start {
-            Instant::now().duration_since(start)
+            moment.saturating_duration_since(start)
         } else {
             Duration::new(0, 0)
         };
-        self.saved + elapsed
+        match self.duration {
+            Some(duration) => Duration::min(self.saved + elapsed, duration),
+            None => self.saved + elapsed,
+        }
+    }
+
+    /// Returns the moment the timer expires,
+    /// if the timer is running and the optional duration is set.
+    pub fn deadline(&self) -> Option<Instant> {
+        let (start, duration) = (self.start?, self.duration?);
+        Some(start + duration.saturating_sub(self.saved))
+    }
+
+    /// Returns the moment the label of the timer changes to the next second, if the timer is running.
+    pub fn next_label_change(&self) -> Option<Instant> {
+        let start = self.start?;
+        let next = Duration::from_secs(self.get_time().as_secs() + 1);
+        Some(start + next.saturating_sub(self.saved))
+    }
+
+    /// Returns the next moment the timer needs attention: when it expires or when its label changes.
+    ///
+    /// If the timer is not running, nothing changes and this function returns [None].
+    pub fn next_deadline(&self) -> Option<Instant> {
+        match (self.deadline(), self.next_label_change()) {
+            (Some(deadline), Some(label)) => Some(Instant::min(deadline, label)),
+            (deadline, label) => deadline.or(label),
+        }
     }
 
     /// Check if the timer has been active longer than the duration
@@ -60,12 +101,9 @@ impl Timer {
     /// Otherwise, the function returns the ratio of the elapsed time to the next full minute.
     pub fn ratio(&self) -> f64 {
         if let Some(duration) = self.duration {
-            return f64::min(
-                self.get_time().as_secs_f64() / duration.as_secs_f64(),
-                1 as f64,
-            );
+            f64::min(self.get_time().as_secs_f64() / duration.as_secs_f64(), 1.0)
         } else {
-            return self.get_time().as_secs_f64() % 60 as f64 / 60 as f64;
+            self.get_time().as_secs_f64() % 60.0 / 60.0
         }
     }
 
@@ -93,8 +131,9 @@ impl Serialize for Timer {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Timer", 1)?;
+        let mut state = serializer.serialize_struct("Timer", 2)?;
         state.serialize_field("total_duration", &self.get_time().as_secs())?;
+        state.serialize_field("total_duration_ms", &self.get_time().as_millis())?;
         state.end()
     }
 }
@@ -0,0 +1,35 @@
+use crate::ui::theme::ThemeConfig;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// The options of the configuration file.
+///
+/// The file is optional, and every option in it can be omitted.
+/// The command line arguments take precedence over the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub theme: Option<String>, // the name of the theme that is used when no theme argument is given
+    pub themes: BTreeMap<String, ThemeConfig>, // custom themes
+    pub bindings: BTreeMap<String, String>, // the keys of the actions, like "restart": "ctrl+t"
+}
+
+impl Config {
+    /// Returns the path of the .json configuration file in the configuration directory.
+    pub fn path() -> Option<PathBuf> {
+        let mut path = dirs::config_dir()?;
+        path.push("type_trainer");
+        path.push("config.json");
+        Some(path)
+    }
+
+    /// Read the configuration file, the default configuration is used if there is no file.
+    pub fn load() -> Result<Config> {
+        match Config::path() {
+            Some(path) if path.exists() => serde_json::from_str(&fs::read_to_string(&path)?)
+                .map_err(|e| anyhow!("Invalid configuration file {}: {}", path.display(), e)),
+            _ => Ok(Config::default()),
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use crate::{app::keys::KeyRecord, stats::NGrams, store::Session};
+use std::{collections::BTreeMap, fmt::Write};
+
+/// The amount of n-grams in the lists of the Markdown and html reports.
+const NGRAM_LIMIT: usize = 10;
+
+/// The formats the history of the exercises can be exported to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Export {
+    Csv,      // a row for every exercise, for spreadsheets
+    Markdown, // a summary with tables, for a wiki
+    Html,     // a summary with charts, that can be opened without a connection
+}
+
+impl Export {
+    /// The names of the formats, as they are given on the command line.
+    pub const NAMES: [&'static str; 3] = ["csv", "md", "html"];
+
+    /// Find a format by its name.
+    pub fn from_name(name: &str) -> Option<Export> {
+        match name {
+            "csv" => Some(Export::Csv),
+            "md" => Some(Export::Markdown),
+            "html" => Some(Export::Html),
+            _ => None,
+        }
+    }
+
+    /// Convert the sessions of the previous exercises and their n-grams into a report of this format.
+    pub fn export(&self, sessions: &[Session], ngrams: &NGrams) -> String {
+        let rows: Vec<Row> = sessions.iter().map(Row::from_session).collect();
+        match self {
+            Export::Csv => csv(&rows),
+            Export::Markdown => markdown(&rows, ngrams),
+            Export::Html => html(&rows, ngrams),
+        }
+    }
+}
+
+/// The results of a single exercise, as they are shown in the reports.
+struct Row {
+    date: String, // the local date and time the exercise was started
+    file: String,
+    seconds: f64,
+    wpm: usize,
+    correct: usize,
+    mistakes: usize,
+    lenient: usize, // the correct characters that are only correct because of a leniency
+    valid: bool,    // false when text was pasted in the exercise
+}
+
+impl Row {
+    /// Read the results of a session of the store.
+    fn from_session(session: &Session) -> Row {
+        Row {
+            date: session.date.format("%Y-%m-%d %H:%M").to_string(),
+            file: session.file().to_string(),
+            seconds: session.duration_ms as f64 / 1000.0,
+            wpm: session.wpm,
+            correct: session.correct,
+            mistakes: session.mistakes,
+            lenient: session.lenient,
+            valid: session.valid,
+        }
+    }
+
+    /// The day of the exercise, the rows are grouped by it.
+    fn day(&self) -> &str {
+        self.date.get(..10).unwrap_or(&self.date)
+    }
+
+    /// The percentage of correct keystrokes.
+    fn accuracy(&self) -> f64 {
+        accuracy(self.correct, self.mistakes)
+    }
+}
+
+/// The combined results of a group of exercises.
+#[derive(Default)]
+struct Summary {
+    count: usize,
+    seconds: f64,
+    wpm: usize, // the sum of the WPM of all the exercises
+    best: usize,
+    correct: usize,
+    mistakes: usize,
+}
+
+impl Summary {
+    /// Add the results of an exercise.
+    fn add(&mut self, row: &Row) {
+        self.count += 1;
+        self.seconds += row.seconds;
+        self.wpm += row.wpm;
+        self.best = usize::max(self.best, row.wpm);
+        self.correct += row.correct;
+        self.mistakes += row.mistakes;
+    }
+
+    /// The average WPM of the exercises.
+    fn average(&self) -> usize {
+        match self.count {
+            0 => 0,
+            count => (self.wpm as f64 / count as f64).round() as usize,
+        }
+    }
+
+    /// The percentage of correct keystrokes of all the exercises.
+    fn accuracy(&self) -> f64 {
+        accuracy(self.correct, self.mistakes)
+    }
+}
+
+/// The summaries of the valid exercises: of all of them, per day and per file.
+///
+/// The exercises with pasted text are left out, their speed is not real.
+fn summarize(rows: &[Row]) -> (Summary, BTreeMap<&str, Summary>, BTreeMap<&str, Summary>) {
+    let mut total = Summary::default();
+    let mut days: BTreeMap<&str, Summary> = BTreeMap::new();
+    let mut files: BTreeMap<&str, Summary> = BTreeMap::new();
+    for row in rows.iter().filter(|row| row.valid) {
+        total.add(row);
+        days.entry(row.day()).or_default().add(row);
+        files.entry(&row.file).or_default().add(row);
+    }
+    (total, days, files)
+}
+
+/// Returns the percentage of correct keystrokes, 100 % when nothing was typed.
+fn accuracy(correct: usize, mistakes: usize) -> f64 {
+    match correct + mistakes {
+        0 => 100.0,
+        total => correct as f64 / total as f64 * 100.0,
+    }
+}
+
+/// Format an amount of seconds in hours and minutes, or minutes and seconds for a short time.
+fn duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match seconds {
+        0..=3599 => format!("{} min {} s", seconds / 60, seconds % 60),
+        _ => format!("{} h {} min", seconds / 3600, seconds / 60 % 60),
+    }
+}
+
+/// Create a CSV file with a row for every exercise.
+fn csv(rows: &[Row]) -> String {
+    let mut csv =
+        String::from("date,file,duration_s,wpm,accuracy,correct,mistakes,lenient,valid\n");
+    for row in rows {
+        let _ = writeln!(
+            csv,
+            "{},{},{:.1},{},{:.1},{},{},{},{}",
+            csv_field(&row.date),
+            csv_field(&row.file),
+            row.seconds,
+            row.wpm,
+            row.accuracy(),
+            row.correct,
+            row.mistakes,
+            row.lenient,
+            row.valid
+        );
+    }
+    csv
+}
+
+/// Quote a field of a CSV file when it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_string(),
+    }
+}
+
+/// Create a Markdown summary with the totals, the progress per day, the files and the weakest bigrams.
+fn markdown(rows: &[Row], ngrams: &NGrams) -> String {
+    let (total, days, files) = summarize(rows);
+    let mut md = String::from("# Typing report\n\n");
+    if total.count == 0 {
+        md.push_str("There are no exercises yet.\n");
+        return md;
+    }
+
+    md.push_str("| | |\n| --- | ---: |\n");
+    let _ = writeln!(md, "| Exercises | {} |", total.count);
+    let _ = writeln!(md, "| Practice time | {} |", duration(total.seconds));
+    let _ = writeln!(md, "| Average WPM | {} |", total.average());
+    let _ = writeln!(md, "| Best WPM | {} |", total.best);
+    let _ = writeln!(md, "| Accuracy | {:.1} % |", total.accuracy());
+
+    md.push_str("\n## Progress per day\n\n");
+    md.push_str("| Day | Exercises | Practice time | Average WPM | Best WPM | Accuracy |\n");
+    md.push_str("| --- | ---: | ---: | ---: | ---: | ---: |\n");
+    for (day, summary) in &days {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {} | {:.1} % |",
+            day,
+            summary.count,
+            duration(summary.seconds),
+            summary.average(),
+            summary.best,
+            summary.accuracy()
+        );
+    }
+
+    md.push_str("\n## Files\n\n");
+    md.push_str("| File | Exercises | Average WPM | Best WPM | Accuracy |\n");
+    md.push_str("| --- | ---: | ---: | ---: | ---: |\n");
+    for (file, summary) in &files {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {} | {} | {:.1} % |",
+            file.replace('|', "\\|"),
+            summary.count,
+            summary.average(),
+            summary.best,
+            summary.accuracy()
+        );
+    }
+
+    md.push_str("\n## Weakest bigrams\n\n");
+    md.push_str("| Bigram | Average time | Mistakes | Typed |\n");
+    md.push_str("| --- | ---: | ---: | ---: |\n");
+    for (bigram, record) in weakest_bigrams(ngrams) {
+        let _ = writeln!(
+            md,
+            "| `{}` | {} ms | {} | {} |",
+            bigram.replace('|', "\\|"),
+            record.speed.average_ms().unwrap_or(0),
+            record.count_fault,
+            record.count_correct + record.count_fault
+        );
+    }
+    md
+}
+
+/// Returns the slowest and the most error-prone bigrams, without duplicates.
+fn weakest_bigrams(ngrams: &NGrams) -> Vec<(&String, &KeyRecord)> {
+    let mut weakest = NGrams::slowest(&ngrams.bigrams, NGRAM_LIMIT);
+    for (bigram, record) in NGrams::error_prone(&ngrams.bigrams, NGRAM_LIMIT) {
+        if !weakest.iter().any(|(other, _)| *other == bigram) {
+            weakest.push((bigram, record));
+        }
+    }
+    weakest
+}
+
+/// Create a self-contained html report with the summary, charts of the progress and the tables of the Markdown report.
+///
+/// The charts are inline svg and the style is embedded, so the file can be opened offline and mailed as it is.
+fn html(rows: &[Row], ngrams: &NGrams) -> String {
+    let (total, days, files) = summarize(rows);
+    let mut html = String::from(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>Typing report</title>
+<style>
+body { font-family: sans-serif; max-width: 52em; margin: 2em auto; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #ddd; }
+td { text-align: right; }
+td:first-child, th:first-child { text-align: left; }
+svg { display: block; margin-bottom: 1.5em; font-size: 12px; }
+</style>
+</head>
+<body>
+<h1>Typing report</h1>
+",
+    );
+    if total.count == 0 {
+        html.push_str("<p>There are no exercises yet.</p>\n</body>\n</html>\n");
+        return html;
+    }
+
+    html.push_str("<table>\n");
+    for (name, value) in [
+        ("Exercises", total.count.to_string()),
+        ("Practice time", duration(total.seconds)),
+        ("Average WPM", total.average().to_string()),
+        ("Best WPM", total.best.to_string()),
+        ("Accuracy", format!("{:.1} %", total.accuracy())),
+    ] {
+        let _ = writeln!(html, "<tr><td>{}</td><td>{}</td></tr>", name, value);
+    }
+    html.push_str("</table>\n");
+
+    let valid: Vec<&Row> = rows.iter().filter(|row| row.valid).collect();
+    let dates: Vec<&str> = valid.iter().map(|row| row.date.as_str()).collect();
+    html.push_str("<h2>Words per minute</h2>\n");
+    html.push_str(&chart(
+        &valid.iter().map(|row| row.wpm as f64).collect::<Vec<f64>>(),
+        &dates,
+        "WPM",
+    ));
+    html.push_str("<h2>Accuracy</h2>\n");
+    html.push_str(&chart(
+        &valid.iter().map(|row| row.accuracy()).collect::<Vec<f64>>(),
+        &dates,
+        "%",
+    ));
+
+    html.push_str("<h2>Progress per day</h2>\n<table>\n");
+    html.push_str("<tr><th>Day</th><th>Exercises</th><th>Practice time</th><th>Average WPM</th><th>Best WPM</th><th>Accuracy</th></tr>\n");
+    for (day, summary) in &days {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1} %</td></tr>",
+            escape(day),
+            summary.count,
+            duration(summary.seconds),
+            summary.average(),
+            summary.best,
+            summary.accuracy()
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Files</h2>\n<table>\n");
+    html.push_str("<tr><th>File</th><th>Exercises</th><th>Average WPM</th><th>Best WPM</th><th>Accuracy</th></tr>\n");
+    for (file, summary) in &files {
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1} %</td></tr>",
+            escape(file),
+            summary.count,
+            summary.average(),
+            summary.best,
+            summary.accuracy()
+        );
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h2>Weakest bigrams</h2>\n<table>\n");
+    html.push_str("<tr><th>Bigram</th><th>Average time</th><th>Mistakes</th><th>Typed</th></tr>\n");
+    for (bigram, record) in weakest_bigrams(ngrams) {
+        let _ = writeln!(
+            html,
+            "<tr><td><code>{}</code></td><td>{} ms</td><td>{}</td><td>{}</td></tr>",
+            escape(bigram),
+            record.speed.average_ms().unwrap_or(0),
+            record.count_fault,
+            record.count_correct + record.count_fault
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Create an svg line chart of the values, with a point for every exercise.
+///
+/// The vertical axis starts at 0, and the label of every point is shown when the mouse is over it.
+fn chart(values: &[f64], labels: &[&str], unit: &str) -> String {
+    const WIDTH: f64 = 640.0;
+    const HEIGHT: f64 = 200.0;
+    const MARGIN: f64 = 40.0;
+
+    let max = values.iter().copied().fold(1.0, f64::max);
+    let step = match values.len() {
+        0 | 1 => 0.0,
+        n => (WIDTH - 2.0 * MARGIN) / (n - 1) as f64,
+    };
+    let point = |i: usize, value: f64| {
+        (
+            MARGIN + i as f64 * step,
+            HEIGHT - MARGIN + (value / max) * (2.0 * MARGIN - HEIGHT),
+        )
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = WIDTH,
+        h = HEIGHT
+    );
+    let _ = writeln!(
+        svg,
+        "<path d=\"M{m} {t} V{b} H{r}\" fill=\"none\" stroke=\"#999\"/>",
+        m = MARGIN,
+        t = MARGIN,
+        b = HEIGHT - MARGIN,
+        r = WIDTH - MARGIN
+    );
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.0} {}</text>",
+        MARGIN - 4.0,
+        MARGIN + 4.0,
+        max,
+        escape(unit)
+    );
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">0</text>",
+        MARGIN - 4.0,
+        HEIGHT - MARGIN + 4.0
+    );
+    if let (Some(first), Some(last)) = (labels.first(), labels.last()) {
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            MARGIN,
+            HEIGHT - MARGIN + 18.0,
+            escape(first)
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{}</text>",
+            WIDTH - MARGIN,
+            HEIGHT - MARGIN + 18.0,
+            escape(last)
+        );
+    }
+
+    let points: Vec<String> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            let (x, y) = point(i, value);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    let _ = writeln!(
+        svg,
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"#2a6fdb\" stroke-width=\"2\"/>",
+        points.join(" ")
+    );
+    for (i, (&value, label)) in values.iter().zip(labels).enumerate() {
+        let (x, y) = point(i, value);
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"#2a6fdb\"><title>{}: {:.0} {}</title></circle>",
+            x,
+            y,
+            escape(label),
+            value,
+            escape(unit)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape the characters that have a meaning in html.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -2,11 +2,35 @@
 /// and the functions to parse the given arguments into the corresponding exercise.
 pub mod parser;
 
+/// Contains the options of the configuration file.
+pub mod config;
+
+/// Contains the functions to read and analyse the statistics of the previous exercises.
+pub mod stats;
+
+/// Contains the versioned schema of the records in the stats file, and the migration of older records.
+pub mod record;
+
+/// Contains the functions to export the statistics of the previous exercises to CSV, Markdown and html reports.
+pub mod export;
+
+/// Contains the indexed store of the history, that is imported from the stats file and answers the queries on it.
+pub mod store;
+
 /// Contains the logic of the type trainer application.
 pub mod app {
+    pub mod bindings;
+    pub mod builtin;
     pub mod content;
     pub mod exercise;
+    pub mod format;
+    pub mod generator;
+    pub mod keys;
+    pub mod layout;
+    pub mod leniency;
+    pub mod menu;
     pub mod prompt;
+    pub mod rolling;
     pub mod settings;
     pub mod state;
     pub mod timer;
@@ -17,4 +41,5 @@ pub mod ui {
     pub mod event;
     pub mod tui;
     pub mod render;
+    pub mod theme;
 }
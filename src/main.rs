@@ -1,8 +1,11 @@
-use anyhow::{Ok, Result};
+use anyhow::{anyhow, Ok, Result};
 use ratatui::{backend::CrosstermBackend, Terminal};
+use std::{fs, path::PathBuf};
 use type_trainer::{
-    app::state::State,
-    parser,
+    app::{exercise::Exercise, menu::Menu, state::State},
+    config::Config,
+    parser, stats,
+    store::Store,
     ui::{
         event::{Event, EventHandler},
         tui::Tui,
@@ -13,39 +16,129 @@ fn main() -> Result<()> {
     // Create the arguments used by the program.
     let matches = parser::create_commands();
 
-    // Create the exercise from the given arguments.
-    let mut exercise = parser::get_exercise(&matches)?;
+    // Save and read the statistics in the given file instead of the default one.
+    if let Some(path) = matches
+        .subcommand_matches("stats")
+        .unwrap_or(&matches)
+        .get_one::<PathBuf>("stats_file")
+    {
+        stats::set_path(path.to_owned());
+    }
+
+    // Print or write the report of the previous exercises instead of starting an exercise.
+    if let Some(matches) = matches.subcommand_matches("stats") {
+        let report = parser::get_report(matches)?;
+        match matches.get_one::<PathBuf>("output") {
+            Some(path) => fs::write(path, report)
+                .map_err(|e| anyhow!("Could not write {}: {}", path.display(), e))?,
+            None => print!("{}", report),
+        }
+        return Ok(());
+    }
+
+    // Read the configuration file.
+    let config = Config::load()?;
+
+    // Create the exercise from the given arguments, or the menu when no path or generator is given.
+    let (exercise, menu) = match matches.contains_id("path") || matches.contains_id("generate") {
+        true => (Some(parser::get_exercise(&matches, &config)?), None),
+        false => (None, Some(parser::get_menu(&matches)?)),
+    };
+    let theme = parser::get_theme(&matches, &config)?;
 
     // Initialize the terminal user interface.
     let backend = CrosstermBackend::new(std::io::stderr());
     let terminal = Terminal::new(backend)?;
-    let events = EventHandler::new(250);
+    let events = EventHandler::new();
 
-    let mut tui = Tui::new(terminal, events);
+    let mut tui = Tui::new(terminal, events, theme);
     tui.enter()?;
 
-    // Start the main loop.
+    if let Some(mut exercise) = exercise {
+        run_exercise(&mut tui, &mut exercise)?;
+    }
+    if let Some(mut menu) = menu {
+        run_menu(&mut tui, &mut menu, &config)?;
+    }
+
+    // Exit the user interface.
+    tui.exit()?;
+
+    Ok(())
+}
+
+/// Show the menu until the user quits.
+///
+/// The exercise of the selected text is started with the settings of the menu,
+/// and the menu is shown again when the user quits the exercise.
+fn run_menu(tui: &mut Tui, menu: &mut Menu, config: &Config) -> Result<()> {
+    tui.draw_menu(menu)?;
+
     loop {
-        match exercise.state {
-            State::Running => exercise.check_timer(),
-            State::Quitting => break,
-            _ => {}
+        match tui.events.next(None)? {
+            Event::Key(key_event) => menu.update(key_event),
+            Event::Resize(_, _) => {}
+            _ => continue,
+        };
+
+        if menu.quit {
+            break;
         }
 
-        // Render the user interface.
-        tui.draw(&mut exercise)?;
+        if let Some(source) = menu.selection.take() {
+            match parser::get_menu_exercise(menu, &source, config) {
+                Result::Ok(mut exercise) => {
+                    run_exercise(tui, &mut exercise)?;
+                    menu.recent = Store::open()
+                        .map(|store| store.recent_files())
+                        .unwrap_or_default();
+                }
+                Err(e) => menu.message = Some(e.to_string()),
+            }
+        }
+
+        tui.draw_menu(menu)?;
+    }
+
+    Ok(())
+}
+
+/// Run the exercise until the user quits.
+fn run_exercise(tui: &mut Tui, exercise: &mut Exercise) -> Result<()> {
+    // Render the initial user interface.
+    tui.draw(exercise)?;
+
+    // Start the main loop.
+    // The user interface is only rendered again when something changed:
+    // after a key event, when the timer expires or its label changes, or when the terminal is resized.
+    loop {
+        let event = tui.events.next(exercise.next_deadline())?;
+
+        // Check the idle time and the timer before the event is handled,
+        // so the idle time is excluded and no keys are counted after the timer expired.
+        if exercise.state == State::Running {
+            exercise.check_idle();
+            exercise.check_timer();
+        }
 
         // Handle events. For this program, only the key events are important.
-        match tui.events.next()? {
+        match event {
             Event::Tick => {}
             Event::Key(key_event) => exercise.update(key_event),
-            Event::Mouse(_) => {}
+            Event::Mouse(_) => continue,
             Event::Resize(_, _) => {}
+            Event::Paste(text) => exercise.paste(&text),
+            Event::FocusLost => exercise.focus_lost(),
+            Event::FocusGained => continue,
         };
-    }
 
-    // Exit the user interface.
-    tui.exit()?;
+        if exercise.state == State::Quitting {
+            break;
+        }
+
+        // Render the user interface.
+        tui.draw(exercise)?;
+    }
 
     Ok(())
 }
@@ -1,16 +1,151 @@
-use crate::app::{content::Content, exercise::Exercise, settings::Settings, timer::Timer};
+use crate::{
+    app::{
+        bindings::KeyBindings,
+        content::{Content, Split},
+        exercise::Exercise,
+        format::{Extract, Format},
+        generator::{Generator, GENERATORS},
+        layout::Layout,
+        leniency::Leniency,
+        menu::{Field, FieldValue, Menu, Source},
+        settings::{Settings, Stop, Target},
+        timer::Timer,
+    },
+    config::Config,
+    export::Export,
+    record,
+    stats,
+    store::{Query, Store},
+    ui::theme::Theme,
+};
 use anyhow::{anyhow, Ok, Result};
-use clap::{command, value_parser, Arg, ArgAction, ArgMatches};
-use std::{fs, path::PathBuf, time::Duration};
+use chrono::NaiveDate;
+use clap::{
+    builder::PossibleValuesParser, command, parser::ValueSource, value_parser, Arg, ArgAction,
+    ArgMatches, Command,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use unicode_normalization::UnicodeNormalization;
 
-/// Specifies all possible command line arguments for the application
+/// Parses the command line arguments of the application
 pub fn create_commands() -> ArgMatches {
+    command().get_matches()
+}
+
+/// Specifies all possible command line arguments for the application
+pub fn command() -> Command {
     command!()
+            .subcommand_negates_reqs(true)
+            .args_conflicts_with_subcommands(true)
+            .subcommand(
+                Command::new("stats")
+                    .about("Show the slowest and the most error-prone bigrams and trigrams of all previous exercises, and the keys of the numeric keypad")
+                    .arg(
+                        Arg::new("limit")
+                            .long("limit")
+                            .short('n')
+                            .help("The amount of n-grams in each list")
+                            .required(false)
+                            .action(ArgAction::Set)
+                            .default_value("10")
+                            .value_parser(value_parser!(u16).range(1..)),
+                    )
+                    .arg(
+                        Arg::new("export")
+                            .long("export")
+                            .short('e')
+                            .value_name("format")
+                            .help("Export the results of all previous exercises instead: a CSV file, a Markdown summary or an html report with charts")
+                            .required(false)
+                            .action(ArgAction::Set)
+                            .value_parser(PossibleValuesParser::new(Export::NAMES)),
+                    )
+                    .arg(
+                        Arg::new("migrate")
+                            .long("migrate")
+                            .help("Upgrade the records of older versions in the stats file, a backup of the file is kept")
+                            .required(false)
+                            .conflicts_with("export")
+                            .action(ArgAction::SetTrue),
+                    )
+                    .arg(
+                        Arg::new("output")
+                            .long("output")
+                            .short('o')
+                            .value_name("file")
+                            .help("Write the report to the given file instead of the standard output")
+                            .required(false)
+                            .action(ArgAction::Set)
+                            .value_parser(value_parser!(PathBuf)),
+                    )
+                    .arg(
+                        Arg::new("file")
+                            .long("file")
+                            .value_name("file")
+                            .help("Only include the exercises of the given file, its full path or only its name")
+                            .required(false)
+                            .conflicts_with("migrate")
+                            .action(ArgAction::Set),
+                    )
+                    .arg(
+                        Arg::new("from")
+                            .long("from")
+                            .value_name("date")
+                            .help("Only include the exercises from the given day on, like 2024-01-31")
+                            .required(false)
+                            .conflicts_with("migrate")
+                            .action(ArgAction::Set)
+                            .value_parser(parse_date),
+                    )
+                    .arg(
+                        Arg::new("to")
+                            .long("to")
+                            .value_name("date")
+                            .help("Only include the exercises up to and including the given day")
+                            .required(false)
+                            .conflicts_with("migrate")
+                            .action(ArgAction::Set)
+                            .value_parser(parse_date),
+                    )
+                    .arg(
+                        Arg::new("settings")
+                            .long("settings")
+                            .value_name("words")
+                            .help("Only include the exercises with all the given settings, like \"dvorak backspace stop=error\"")
+                            .required(false)
+                            .conflicts_with("migrate")
+                            .action(ArgAction::Set),
+                    ),
+            )
             .arg(
                 Arg::new("path")
                     .index(1)
-                    .help("The path to the file you want to use for training")
-                    .required(true)
+                    .help("The path to the file you want to use for training, a menu is shown when it is omitted")
+                    .required(false)
+                    .value_parser(value_parser!(PathBuf)),
+            )
+            .arg(
+                Arg::new("generate")
+                    .long("generate")
+                    .value_name("drill")
+                    .help("Train with generated numbers, amounts with separators or code symbols instead of a file, the amount of lines is set by --prompts")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .conflicts_with("path")
+                    .value_parser(PossibleValuesParser::new(GENERATORS.iter().map(|generator| generator.name))),
+            )
+            .arg(
+                Arg::new("stats_file")
+                    .long("stats-file")
+                    .value_name("file")
+                    .help(format!("The file where the results of the exercises are saved, instead of the file in the cache directory or of {}", stats::STATS_FILE_VAR))
+                    .required(false)
+                    .global(true)
+                    .action(ArgAction::Set)
                     .value_parser(value_parser!(PathBuf)),
             )
             .arg(
@@ -41,6 +176,47 @@ pub fn create_commands() -> ArgMatches {
                     .action(ArgAction::Set)
                     .value_parser(value_parser!(u16).range(1..)),
             )
+            .arg(
+                Arg::new("idle")
+                    .long("idle")
+                    .short('e')
+                    .value_name("seconds")
+                    .help("Pause the exercise when nothing is typed for the given amount of seconds, the idle time is not counted")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .value_parser(value_parser!(u16).range(1..)),
+            )
+            .arg(
+                Arg::new("word_target")
+                    .long("word-target")
+                    .value_name("words")
+                    .help("Finish the exercise after the given amount of words, independent of the prompts")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .conflicts_with("char_target")
+                    .value_parser(value_parser!(u32).range(1..)),
+            )
+            .arg(
+                Arg::new("char_target")
+                    .long("char-target")
+                    .value_name("characters")
+                    .help("Finish the exercise after the given amount of characters, independent of the prompts")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .value_parser(value_parser!(u32).range(1..)),
+            )
+            .arg(
+                Arg::new("endless")
+                    .long("endless")
+                    .short('z')
+                    .value_name("minutes")
+                    .help("Cycle through the prompts without end, the WPM and the ratio are calculated over the last minutes")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .num_args(0..=1)
+                    .default_missing_value("1")
+                    .value_parser(value_parser!(u16).range(1..)),
+            )
             .arg(
                 Arg::new("terminate")
                     .long("terminate")
@@ -55,10 +231,49 @@ pub fn create_commands() -> ArgMatches {
                 Arg::new("words")
                     .long("words")
                     .short('w')
-                    .help("Split every word of the text into a separate prompt")
+                    .help("Split every word of the text into a separate prompt, the same as --split word")
                     .required(false)
+                    .conflicts_with("split")
                     .action(ArgAction::SetTrue),
             )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .help("The format of the file, the markup of Markdown and html is stripped, and the comments or the code of a source file are extracted")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .default_value("auto")
+                    .value_parser(PossibleValuesParser::new(Format::NAMES)),
+            )
+            .arg(
+                Arg::new("extract")
+                    .long("extract")
+                    .help("The part of a Markdown or source file to type: the text or comments, the code, or all of it")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .default_value("text")
+                    .value_parser(PossibleValuesParser::new(Extract::NAMES)),
+            )
+            .arg(
+                Arg::new("split")
+                    .long("split")
+                    .value_name("unit")
+                    .help("Split the text into prompts of a line, word, sentence, paragraph or chars:N, the last three reflow the wrapped lines")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .default_value("line")
+                    .value_parser(parse_split),
+            )
+            .arg(
+                Arg::new("max_length")
+                    .long("max-length")
+                    .value_name("characters")
+                    .help("Break the sentences and paragraphs that are longer than the given amount of characters at the words")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .default_value("120")
+                    .value_parser(value_parser!(u16).range(10..)),
+            )
             .arg(
                 Arg::new("random")
                     .long("random")
@@ -75,6 +290,13 @@ pub fn create_commands() -> ArgMatches {
                     .required(false)
                     .action(ArgAction::SetFalse),
             )
+            .arg(
+                Arg::new("word_backspace")
+                    .long("word-backspace")
+                    .help("Only allow the backspace within the current word")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
             .arg(   
                 Arg::new("blind")
                     .long("blind")
@@ -115,34 +337,182 @@ pub fn create_commands() -> ArgMatches {
                     .required(false)
                     .action(ArgAction::SetTrue),
             )
-            .get_matches()
+            .arg(
+                Arg::new("stop")
+                    .long("stop")
+                    .value_name("mode")
+                    .help("Stop the cursor on a wrong character until it is fixed, or on a word with a mistake in it")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .default_value("off")
+                    .value_parser(PossibleValuesParser::new(Stop::NAMES)),
+            )
+            .arg(
+                Arg::new("ignore_case")
+                    .long("ignore-case")
+                    .help("Accept the letters that are typed in the wrong case")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("ignore_punctuation")
+                    .long("ignore-punctuation")
+                    .help("Skip the punctuation of the prompt when it is not typed, and accept any punctuation for another")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("ascii_quotes")
+                    .long("ascii-quotes")
+                    .help("Accept the ASCII quotes, dashes and spaces for their typographic equivalents")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("fold_accents")
+                    .long("fold-accents")
+                    .help("Accept the letters without their accents")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("inline")
+                    .long("inline")
+                    .short('i')
+                    .help("Type over the text of the prompt instead of in a separate box")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("paste")
+                    .long("paste")
+                    .help("Accept pasted text as typed text, the exercise is still marked as invalid")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("drill")
+                    .long("drill")
+                    .short('g')
+                    .help("Drill the slowest and most error-prone bigrams and trigrams of the previous exercises with the words of the text")
+                    .required(false)
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("layout")
+                    .long("layout")
+                    .short('l')
+                    .help("The keyboard layout that is used to analyse the mistakes and speed of each finger")
+                    .required(false)
+                    .action(ArgAction::Set)
+                    .default_value(Layout::QWERTY.name)
+                    .value_parser(PossibleValuesParser::new(Layout::ALL.map(|l| l.name))),
+            )
+            .arg(
+                Arg::new("theme")
+                    .long("theme")
+                    .help(format!("The colour theme of the interface: {}, or a custom theme from the configuration file", Theme::NAMES.join(", ")))
+                    .required(false)
+                    .action(ArgAction::Set),
+            )
 }
 
 /// Parse the command line arguments to create the [Exercise]
-pub fn get_exercise(matches: &ArgMatches) -> Result<Exercise> {
+pub fn get_exercise(matches: &ArgMatches, config: &Config) -> Result<Exercise> {
+    // The text of a generator is created with the content
+    if let Some(name) = matches.get_one::<String>("generate") {
+        return build_exercise(matches, config, Path::new(name), String::new());
+    }
+
+    let path = matches
+        .get_one::<PathBuf>("path")
+        .expect("The exercise is only created from the arguments when a path is given");
+    let text = fs::read_to_string(path)?;
+
+    // The full path is saved, so the file can be found again in the recent files of the menu
+    let path = fs::canonicalize(path).unwrap_or(path.to_owned());
+
+    build_exercise(matches, config, &path, text)
+}
+
+/// Create the [Exercise] of the text that was selected in the [Menu], with the settings of the menu
+pub fn get_menu_exercise(menu: &Menu, source: &Source, config: &Config) -> Result<Exercise> {
+    let (path, text) = match source {
+        Source::File(path) => (
+            path.to_owned(),
+            fs::read_to_string(path)
+                .map_err(|e| anyhow!("Could not read {}: {}", path.display(), e))?,
+        ),
+        Source::Builtin(builtin) => (PathBuf::from(builtin.name), builtin.text.to_string()),
+        Source::Generator(generator) => (PathBuf::from(generator.name), String::new()),
+    };
+
+    // Only the first line of the error is shown, the usage is not relevant in the menu
+    let matches = command()
+        .try_get_matches_from(menu.arguments(source))
+        .map_err(|e| anyhow!("{}", e.to_string().lines().next().unwrap_or_default()))?;
+
+    build_exercise(&matches, config, &path, text)
+}
+
+/// Create the [Exercise] of the given text with the command line arguments and the configuration file
+fn build_exercise(
+    matches: &ArgMatches,
+    config: &Config,
+    path: &Path,
+    text: String,
+) -> Result<Exercise> {
     let timer = get_timer(matches)?;
-    let content = get_content(matches)?;
+    let content = get_content(matches, path, text)?;
     let settings = get_settings(matches)?;
+    let bindings = get_bindings(config)?;
 
-    Ok(Exercise::build(timer, content, settings))
+    Ok(Exercise::build(timer, content, settings, bindings))
 }
 
-/// Parse the command line arguments to create the [Content]
-pub fn get_content(matches: &ArgMatches) -> Result<Content> {
-    let path = matches
-        .get_one::<PathBuf>("path")
-        .expect("Path is required");
+/// Parse the command line arguments to create the [Content] of the given text
+pub fn get_content(matches: &ArgMatches, path: &Path, content: String) -> Result<Content> {
+    // A generated text replaces the text of the file, it has as many lines as the limit of the prompts
+    let content = match matches
+        .get_one::<String>("generate")
+        .and_then(|name| Generator::from_name(name))
+    {
+        Some(generator) => {
+            let lines = matches
+                .get_one::<u32>("prompts")
+                .map_or(20, |&p| p as usize);
+            generator.text(lines)
+        }
+        None => content,
+    };
 
-    let content = fs::read_to_string(path)?;
-    let words = matches.get_flag("words");
+    let split = match matches.get_flag("words") {
+        true => Split::Word,
+        false => *matches
+            .get_one::<Split>("split")
+            .expect("The split has a default value"),
+    };
+    let limit = *matches
+        .get_one::<u16>("max_length")
+        .expect("The maximum length has a default value") as usize;
 
-    let mut prompts = Content::into_prompts(content, words);
+    let format = matches
+        .get_one::<String>("format")
+        .and_then(|name| Format::from_name(name, path))
+        .unwrap_or(Format::Plain);
+    let extract = matches
+        .get_one::<String>("extract")
+        .and_then(|name| Extract::from_name(name))
+        .unwrap_or(Extract::Text);
+    // The text is normalized before it is split, so the lengths of the prompts are counted in composed characters
+    let content: String = format.extract(&content, extract).nfc().collect();
+
+    let mut prompts = Content::into_prompts(content, split, limit);
 
     if prompts.is_empty() {
         return Err(anyhow!(
             "Could't create any prompts from the file at {}",
-            path.to_str()
-                .expect("An invalid path should be caught when we read the file to a string")
+            path.display()
         ));
     }
 
@@ -164,9 +534,162 @@ pub fn get_content(matches: &ArgMatches) -> Result<Content> {
         }
     }
 
+    // Replace the prompts with a drill of the weakest n-grams
+    if matches.get_flag("drill") {
+        let ngrams = Store::open()?.ngrams(&Query::default())?.weakest(10);
+        if ngrams.is_empty() {
+            return Err(anyhow!(
+                "There are not enough statistics of previous exercises to create a drill"
+            ));
+        }
+        prompts = stats::drill(&prompts, &ngrams);
+    }
+
     let random = matches.get_flag("random");
 
-    Ok(Content::build(path.to_owned(), prompts, random, words))
+    let endless = matches.get_one::<u16>("endless").is_some();
+
+    Ok(Content::build(
+        path.to_owned(),
+        prompts,
+        random,
+        split,
+        endless,
+    ))
+}
+
+/// Parse the value of the split argument, the amount of characters is given as "chars:N"
+fn parse_split(value: &str) -> Result<Split> {
+    Split::from_name(value).ok_or_else(|| {
+        anyhow!("expected line, word, sentence, paragraph or chars:N with N above 0")
+    })
+}
+
+/// Create the [Menu] with the recent files of the previous exercises,
+/// and a settings form with the arguments of the exercise that were given on the command line
+pub fn get_menu(matches: &ArgMatches) -> Result<Menu> {
+    // The words flag is left out, it is the same as the split "word" and it conflicts with the split field
+    let fields = command()
+        .get_arguments()
+        .filter(|arg| {
+            !matches!(
+                arg.get_id().as_str(),
+                "path" | "generate" | "stats_file" | "theme" | "words"
+            )
+        })
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str();
+            let value = match arg.get_action() {
+                ArgAction::SetTrue | ArgAction::SetFalse => {
+                    FieldValue::Flag(matches.value_source(id) == Some(ValueSource::CommandLine))
+                }
+                ArgAction::Set if id == "split" => {
+                    let mut values: Vec<String> =
+                        Split::PRESETS.iter().map(|value| value.to_string()).collect();
+                    let value = matches
+                        .get_one::<Split>(id)
+                        .map(|split| split.to_string())
+                        .unwrap_or_default();
+                    let index = match values.iter().position(|v| *v == value) {
+                        Some(index) => index,
+                        None => {
+                            values.push(value);
+                            values.len() - 1
+                        }
+                    };
+                    FieldValue::Choice(values, index)
+                }
+                ArgAction::Set if arg.get_possible_values().is_empty() => {
+                    let value = matches
+                        .get_raw(id)
+                        .and_then(|mut values| values.next())
+                        .and_then(|value| value.to_str()?.parse().ok());
+                    let step = match id {
+                        "duration" | "char_target" => 30,
+                        "idle" | "prompts" => 5,
+                        "max_length" => 20,
+                        "word_target" => 10,
+                        _ => 1,
+                    };
+                    FieldValue::Number(value, step)
+                }
+                ArgAction::Set => {
+                    let values: Vec<String> = arg
+                        .get_possible_values()
+                        .iter()
+                        .map(|value| value.get_name().to_string())
+                        .collect();
+                    let index = matches
+                        .get_one::<String>(id)
+                        .and_then(|value| values.iter().position(|v| v == value))
+                        .unwrap_or(0);
+                    FieldValue::Choice(values, index)
+                }
+                _ => return None,
+            };
+            Some(Field {
+                long: arg.get_long()?.to_string(),
+                help: arg
+                    .get_help()
+                    .map(|help| help.to_string())
+                    .unwrap_or_default(),
+                value,
+            })
+        })
+        .collect();
+
+    // Without a readable history the menu is still shown, only without recent files
+    let recent = Store::open()
+        .map(|store| store.recent_files())
+        .unwrap_or_default();
+    Menu::build(recent, fields)
+}
+
+/// Parse the arguments of the stats command to create the report of the previous exercises,
+/// the export of all their results in the given format, or to migrate the stats file
+pub fn get_report(matches: &ArgMatches) -> Result<String> {
+    if matches.get_flag("migrate") {
+        return Ok(match stats::migrate_history()? {
+            0 => String::from("All records are up to date\n"),
+            upgraded => format!(
+                "Upgraded {} records to version {}, the original file is kept at {}\n",
+                upgraded,
+                record::VERSION,
+                stats::backup_path(&stats::path()?).display()
+            ),
+        });
+    }
+
+    let store = Store::open()?;
+    let query = Query {
+        file: matches.get_one::<String>("file").cloned(),
+        from: matches.get_one::<NaiveDate>("from").copied(),
+        to: matches.get_one::<NaiveDate>("to").copied(),
+        settings: matches
+            .get_one::<String>("settings")
+            .map(|words| words.split_whitespace().map(String::from).collect())
+            .unwrap_or_default(),
+    };
+    let ngrams = store.ngrams(&query)?;
+
+    if let Some(export) = matches
+        .get_one::<String>("export")
+        .and_then(|name| Export::from_name(name))
+    {
+        return Ok(export.export(&store.sessions(&query)?, &ngrams));
+    }
+
+    let limit = *matches
+        .get_one::<u16>("limit")
+        .expect("The limit has a default value") as usize;
+
+    Ok(ngrams.report(limit))
+}
+
+/// Parse a day of the query of the stats command, like 2024-01-31
+fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow!("expected a date like 2024-01-31"))
 }
 
 /// Parse the command line arguments to create the [Settings]
@@ -177,21 +700,82 @@ pub fn get_settings(matches: &ArgMatches) -> Result<Settings> {
     let auto = matches.get_flag("auto");
     let correct = matches.get_flag("correct");
     let repeat = matches.get_flag("repeat");
-    let terminate = match matches.get_one::<u16>("terminate") {
-        Some(&t) => Some(t as usize),
-        None => Option::None,
+    let terminate = matches
+        .get_one::<u16>("terminate")
+        .map(|&t| t as usize);
+
+    let layout = matches
+        .get_one::<String>("layout")
+        .and_then(|name| Layout::from_name(name))
+        .unwrap_or_default();
+
+    let inline = matches.get_flag("inline");
+    let paste = matches.get_flag("paste");
+    let idle = matches
+        .get_one::<u16>("idle")
+        .map(|&i| Duration::from_secs(i as u64));
+    let word_backspace = matches.get_flag("word_backspace");
+    let endless = matches
+        .get_one::<u16>("endless")
+        .map(|&m| Duration::from_secs(m as u64 * 60));
+    let target = match (
+        matches.get_one::<u32>("word_target"),
+        matches.get_one::<u32>("char_target"),
+    ) {
+        (Some(&words), _) => Some(Target::Words(words as usize)),
+        (_, Some(&chars)) => Some(Target::Chars(chars as usize)),
+        _ => None,
+    };
+    let stop = matches
+        .get_one::<String>("stop")
+        .and_then(|name| Stop::from_name(name));
+    let leniency = Leniency {
+        case: matches.get_flag("ignore_case"),
+        punctuation: matches.get_flag("ignore_punctuation"),
+        typography: matches.get_flag("ascii_quotes"),
+        accents: matches.get_flag("fold_accents"),
     };
 
-    Ok(Settings::build(
-        backspace, highlight, blind, auto, correct, repeat, terminate,
-    ))
+    Ok(Settings {
+        highlight,
+        blind,
+        backspace,
+        word_backspace,
+        auto,
+        correct,
+        repeat,
+        stop,
+        leniency,
+        terminate,
+        target,
+        layout,
+        inline,
+        paste,
+        idle,
+        endless,
+        changed: false,
+    })
+}
+
+/// Parse the command line arguments and the configuration file to create the [Theme]
+pub fn get_theme(matches: &ArgMatches, config: &Config) -> Result<Theme> {
+    let name = matches
+        .get_one::<String>("theme")
+        .or(config.theme.as_ref())
+        .map_or("dark", |name| name.as_str());
+
+    Theme::from_name(name, &config.themes)
+}
+
+/// Parse the configuration file to create the [KeyBindings]
+pub fn get_bindings(config: &Config) -> Result<KeyBindings> {
+    KeyBindings::from_config(&config.bindings)
 }
 
 /// Parse the command line arguments to create the [Timer]
 pub fn get_timer(matches: &ArgMatches) -> Result<Timer> {
-    let duration = match matches.get_one::<u16>("duration") {
-        Some(d) => Some(Duration::from_secs(*d as u64)),
-        None => Option::None,
-    };
+    let duration = matches
+        .get_one::<u16>("duration")
+        .map(|&d| Duration::from_secs(d as u64));
     Ok(Timer::new(duration))
 }
@@ -0,0 +1,241 @@
+use crate::app::keys::{Interval, KeyRecord};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, time::Duration};
+
+/// The version of the records that are written by this version of the application.
+///
+/// - version 0: the records without a version field, the date is written in RFC 2822
+/// - version 1: the version field is added, the date is written in RFC 3339
+/// - version 2: the results of the finished prompts are added
+///
+/// When the shape of a record changes, the version is raised and a step is added to [MIGRATIONS].
+pub const VERSION: u64 = 2;
+
+/// The steps that upgrade a record to the next version, the step at index n upgrades version n to n + 1.
+const MIGRATIONS: [fn(&mut Value) -> Result<()>; VERSION as usize] = [to_version_1, to_version_2];
+
+/// The record of a single exercise, every line of the stats file contains one.
+///
+/// The fields that are missing in a record are read as their default value,
+/// so the records of older versions stay readable when fields are added.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Record {
+    pub version: u64,
+    #[serde(with = "rfc3339")]
+    pub date: DateTime<FixedOffset>, // the moment the exercise was started, with the offset of the local time
+    pub timer: TimerRecord,
+    pub content: ContentRecord,
+    pub settings: SettingsRecord,
+    pub stats: StatsRecord,
+    pub prompts: Vec<PromptCounts>, // the finished prompts, in the order they were typed
+    pub wpm: usize,
+    pub valid: bool,   // false when text was pasted in the exercise
+    pub pasted: usize, // the amount of pasted characters
+}
+
+/// The duration of the exercise.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimerRecord {
+    pub total_duration: u64, // in seconds
+    pub total_duration_ms: u64,
+}
+
+/// The text of the exercise and how it was split into prompts.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ContentRecord {
+    pub file_path: Option<String>, // the name of the file
+    pub path: Option<String>, // the full path of the file, or the name of a built-in or generated exercise
+    pub words: bool,          // true when every word was a prompt, the same as the split "word"
+    pub split: Option<String>,
+    pub random: bool,
+    pub endless: bool,
+}
+
+/// The settings the exercise was typed with.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SettingsRecord {
+    pub blind: bool,
+    pub backspace: bool,
+    pub word_backspace: bool,
+    pub highlight: bool,
+    pub auto: bool,
+    pub correct: bool,
+    pub repeat: bool,
+    pub stop: Option<String>,
+    pub leniency: LeniencyRecord,
+    pub terminate: Option<usize>,
+    pub target: Option<String>, // the amount and the unit, like "50 words"
+    pub layout: Option<String>,
+    pub inline: bool,
+    pub paste: bool,
+    pub idle: Option<u64>,    // in seconds
+    pub endless: Option<u64>, // the window of the rolling statistics in minutes
+    pub changed: bool,        // true when the settings were changed in the settings overlay
+}
+
+/// The differences with the prompt that were not counted as mistakes.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LeniencyRecord {
+    pub case: bool,
+    pub punctuation: bool,
+    pub typography: bool,
+    pub accents: bool,
+}
+
+/// The counters of the typed characters.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StatsRecord {
+    pub count_correct: usize,
+    pub count_correct_remove: usize, // the correct characters that were removed again
+    pub count_fault: usize,
+    pub count_fault_remove: usize, // the mistakes that were removed again
+    pub count_lenient: usize, // the correct characters that are only correct because of a leniency
+    pub keys: KeyStatsRecord,
+}
+
+/// The statistics of the keys, the n-grams, the fingers and the hands.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyStatsRecord {
+    pub keys: BTreeMap<String, KeyCounts>,
+    pub bigrams: BTreeMap<String, KeyCounts>,
+    pub trigrams: BTreeMap<String, KeyCounts>,
+    pub fingers: BTreeMap<String, KeyCounts>,
+    pub hands: BTreeMap<String, KeyCounts>,
+    pub same_finger: IntervalRecord,
+    pub same_hand: IntervalRecord,
+    pub alternation: IntervalRecord,
+    pub keypad: BTreeMap<String, KeyCounts>, // the keys typed on the numeric keypad
+}
+
+/// The counters of a key, an n-gram or a group of keys.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct KeyCounts {
+    pub count_correct: usize,
+    pub count_fault: usize,
+    pub count_timed: usize, // the correct keystrokes of which the interval was measured
+    pub average_ms: Option<u64>,
+}
+
+/// The result of a single finished prompt.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PromptCounts {
+    pub chars: usize, // the length of the prompt
+    pub correct: usize,
+    pub mistakes: usize,
+    pub time_ms: u64, // the time between the end of the previous prompt and the end of this one
+}
+
+/// The amount and the average of a number of measured intervals.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct IntervalRecord {
+    pub count: usize,
+    pub average_ms: Option<u64>,
+}
+
+impl From<&KeyCounts> for KeyRecord {
+    fn from(counts: &KeyCounts) -> KeyRecord {
+        KeyRecord {
+            count_correct: counts.count_correct,
+            count_fault: counts.count_fault,
+            speed: Interval {
+                count: counts.count_timed,
+                total: Duration::from_millis(
+                    counts.average_ms.unwrap_or(0) * counts.count_timed as u64,
+                ),
+            },
+        }
+    }
+}
+
+impl Record {
+    /// Parse a line of the stats file, a record of an older version is upgraded first.
+    pub fn parse(line: &str) -> Result<Record> {
+        let value = migrate(serde_json::from_str(line)?)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+/// Returns the version of a record, the records without a version field are version 0.
+pub fn version(record: &Value) -> u64 {
+    record["version"].as_u64().unwrap_or(0)
+}
+
+/// Upgrade a record to the current [VERSION].
+///
+/// The record is changed as little as possible, the fields that are unknown to this version are kept.
+/// A record of a newer version can not be downgraded, so it is returned as an error.
+pub fn migrate(mut record: Value) -> Result<Value> {
+    let version = version(&record);
+    if version > VERSION {
+        return Err(anyhow!(
+            "The record has version {}, this application only knows version {}",
+            version,
+            VERSION
+        ));
+    }
+    if !record.is_object() {
+        return Err(anyhow!("The record is not a JSON object"));
+    }
+    for step in &MIGRATIONS[version as usize..] {
+        step(&mut record)?;
+    }
+    Ok(record)
+}
+
+/// Version 1: add the version field and write the date in RFC 3339 instead of RFC 2822.
+///
+/// The oldest records have no valid field, they were written before pasting was detected.
+fn to_version_1(record: &mut Value) -> Result<()> {
+    let date = record["date"]
+        .as_str()
+        .ok_or_else(|| anyhow!("The record has no date"))?;
+    let date =
+        DateTime::parse_from_rfc2822(date).map_err(|e| anyhow!("Invalid date {}: {}", date, e))?;
+    record["date"] = Value::from(date.to_rfc3339_opts(SecondsFormat::Secs, false));
+    if record["valid"].is_null() {
+        record["valid"] = Value::from(true);
+    }
+    record["version"] = Value::from(1);
+    Ok(())
+}
+
+/// Version 2: the results of the prompts were not recorded before, so the list is empty.
+fn to_version_2(record: &mut Value) -> Result<()> {
+    record["prompts"] = Value::Array(Vec::new());
+    record["version"] = Value::from(2);
+    Ok(())
+}
+
+/// Serialize the date of a record in RFC 3339, like 2024-01-31T18:30:00+01:00.
+pub mod rfc3339 {
+    use chrono::{DateTime, FixedOffset, SecondsFormat};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(date: &DateTime<FixedOffset>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&date.to_rfc3339_opts(SecondsFormat::Secs, false))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let date = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&date).map_err(D::Error::custom)
+    }
+}
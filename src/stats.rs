@@ -0,0 +1,333 @@
+use crate::{
+    app::keys::KeyRecord,
+    record::{self, Record},
+};
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsStr,
+    fmt::Write,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Read, Seek, SeekFrom, Write as _},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+/// The minimal amount of times an n-gram has to be typed before it is included in a report.
+const MIN_SAMPLES: usize = 3;
+
+/// The environment variable with the path of the stats file, it is used when no stats file argument is given.
+pub const STATS_FILE_VAR: &str = "TYPE_TRAINER_STATS";
+
+/// The stats file of the command line arguments, it takes precedence over the environment variable.
+static STATS_FILE: OnceLock<PathBuf> = OnceLock::new();
+
+/// The time to wait for another run of the application to release the lock of the stats file.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A lock that is older than this was left behind by a run that was killed while it was writing.
+const STALE_LOCK: Duration = Duration::from_secs(30);
+
+/// Use the given stats file instead of the default one.
+pub fn set_path(path: PathBuf) {
+    let _ = STATS_FILE.set(path);
+}
+
+/// Returns the path of the .json file where the stats of every exercise are saved.
+///
+/// This is the file of the stats file argument or the environment variable,
+/// or stats.json in the type_trainer directory of the cache directory.
+pub fn path() -> Result<PathBuf> {
+    if let Some(path) = STATS_FILE.get() {
+        return Ok(path.to_owned());
+    }
+    if let Some(path) = env::var_os(STATS_FILE_VAR).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let mut path = dirs::cache_dir().ok_or_else(|| {
+        anyhow!(
+            "There is no cache directory for the statistics, choose a file with --stats-file or {}",
+            STATS_FILE_VAR
+        )
+    })?;
+    path.push("type_trainer");
+    path.push("stats.json");
+    Ok(path)
+}
+
+/// Returns the path of a file next to the stats file, with the given suffix after its name.
+pub fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(OsStr::new(suffix));
+    PathBuf::from(name)
+}
+
+/// Append the record of an exercise to the stats file, the directory of the file is created when it is missing.
+///
+/// The file is locked while the record is written, so the records of concurrent runs do not interleave.
+/// A record that could only be written partly is removed again, so the file never ends with a broken line.
+/// Returns the path of the stats file.
+pub fn append(record: &str) -> Result<PathBuf> {
+    let path = path()?;
+    if let Some(directory) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(directory)
+            .map_err(|e| anyhow!("Could not create {}: {}", directory.display(), e))?;
+    }
+
+    let _lock = Lock::acquire(&path)?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(&path)
+        .map_err(|e| anyhow!("Could not open {}: {}", path.display(), e))?;
+    let length = file.metadata()?.len();
+
+    let mut line = String::new();
+    // A line that was broken off by an older version is ended first, so it does not corrupt this record
+    if length > 0 {
+        let mut last = [0];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last)?;
+        if last[0] != b'\n' {
+            line.push('\n');
+        }
+    }
+    line.push_str(record);
+    line.push('\n');
+
+    if let Err(e) = file
+        .write_all(line.as_bytes())
+        .and_then(|_| file.sync_data())
+    {
+        let _ = file.set_len(length);
+        return Err(anyhow!("Could not write to {}: {}", path.display(), e));
+    }
+    Ok(path)
+}
+
+/// A lock file next to the stats file, the lock is released when it is dropped.
+pub struct Lock {
+    path: PathBuf,
+}
+
+impl Lock {
+    /// Create the lock file, or wait until the other run that created it removes it.
+    pub fn acquire(stats: &Path) -> Result<Lock> {
+        let path = sibling(stats, ".lock");
+        let start = Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => return Ok(Lock { path }),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+                    if age.is_some_and(|age| age > STALE_LOCK) {
+                        let _ = fs::remove_file(&path);
+                    } else if start.elapsed() > LOCK_TIMEOUT {
+                        return Err(anyhow!(
+                            "The stats file is locked by another run, remove {} if there is none",
+                            path.display()
+                        ));
+                    } else {
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                }
+                Err(e) => return Err(anyhow!("Could not lock {}: {}", path.display(), e)),
+            }
+        }
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Upgrade the records of older versions in the stats file to the current [record::VERSION].
+///
+/// The original file is kept as a backup next to it, and the new file replaces it at once.
+/// Lines that can not be upgraded are kept as they are. Returns the amount of upgraded records.
+pub fn migrate_history() -> Result<usize> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(0);
+    }
+    let _lock = Lock::acquire(&path)?;
+    let mut upgraded = 0;
+    let lines: Vec<String> = fs::read_to_string(&path)?
+        .lines()
+        .map(|line| match serde_json::from_str::<Value>(line) {
+            Ok(value) if record::version(&value) < record::VERSION => {
+                match record::migrate(value) {
+                    Ok(value) if Record::deserialize(&value).is_ok() => {
+                        upgraded += 1;
+                        value.to_string()
+                    }
+                    _ => line.to_string(),
+                }
+            }
+            _ => line.to_string(),
+        })
+        .collect();
+
+    if upgraded > 0 {
+        fs::copy(&path, backup_path(&path))?;
+        let temporary = sibling(&path, ".tmp");
+        fs::write(&temporary, lines.join("\n") + "\n")?;
+        fs::rename(&temporary, &path)?;
+    }
+    Ok(upgraded)
+}
+
+/// Returns the path of the copy of the stats file that is made before it is migrated.
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".bak")
+}
+
+/// The bigrams and trigrams of all the previous exercises combined.
+#[derive(Default)]
+pub struct NGrams {
+    pub bigrams: BTreeMap<String, KeyRecord>,
+    pub trigrams: BTreeMap<String, KeyRecord>,
+    pub keypad: BTreeMap<String, KeyRecord>, // the keys that were typed on the numeric keypad
+}
+
+impl NGrams {
+    /// Returns the n-grams with the highest average time, the slowest n-gram first.
+    pub fn slowest(
+        ngrams: &BTreeMap<String, KeyRecord>,
+        limit: usize,
+    ) -> Vec<(&String, &KeyRecord)> {
+        let mut slowest: Vec<(&String, &KeyRecord)> = ngrams
+            .iter()
+            .filter(|(_, record)| record.speed.count >= MIN_SAMPLES)
+            .collect();
+        slowest.sort_by_key(|(_, record)| std::cmp::Reverse(record.speed.average_ms()));
+        slowest.truncate(limit);
+        slowest
+    }
+
+    /// Returns the n-grams with the highest ratio of mistakes, the most error-prone n-gram first.
+    pub fn error_prone(
+        ngrams: &BTreeMap<String, KeyRecord>,
+        limit: usize,
+    ) -> Vec<(&String, &KeyRecord)> {
+        let mut error_prone: Vec<(&String, &KeyRecord)> = ngrams
+            .iter()
+            .filter(|(_, record)| {
+                record.count_fault > 0 && record.count_correct + record.count_fault >= MIN_SAMPLES
+            })
+            .collect();
+        error_prone.sort_by(|(_, a), (_, b)| fault_ratio(b).total_cmp(&fault_ratio(a)));
+        error_prone.truncate(limit);
+        error_prone
+    }
+
+    /// Returns the weakest n-grams: the slowest and the most error-prone bigrams and trigrams.
+    pub fn weakest(&self, limit: usize) -> Vec<String> {
+        let mut weakest: Vec<String> = Vec::new();
+        for ngrams in [&self.trigrams, &self.bigrams] {
+            for (ngram, _) in Self::error_prone(ngrams, limit)
+                .into_iter()
+                .chain(Self::slowest(ngrams, limit))
+            {
+                // Skip the whitespace, these transitions are part of every text
+                if !weakest.contains(ngram) && !ngram.contains(char::is_whitespace) {
+                    weakest.push(ngram.clone());
+                }
+            }
+        }
+        weakest
+    }
+
+    /// Create a report of the slowest and the most error-prone bigrams and trigrams, and the keys of the numeric keypad.
+    pub fn report(&self, limit: usize) -> String {
+        let mut report = String::new();
+        for (name, ngrams) in [("bigrams", &self.bigrams), ("trigrams", &self.trigrams)] {
+            let _ = writeln!(report, "Slowest {}:", name);
+            for (ngram, record) in Self::slowest(ngrams, limit) {
+                let _ = writeln!(
+                    report,
+                    "  {:<5} {:>6} ms  ({} times)",
+                    format!("{:?}", ngram),
+                    record.speed.average_ms().unwrap_or(0),
+                    record.speed.count
+                );
+            }
+            let _ = writeln!(report, "Most error-prone {}:", name);
+            for (ngram, record) in Self::error_prone(ngrams, limit) {
+                let _ = writeln!(
+                    report,
+                    "  {:<5} {:>5.1} %  ({} of {} times)",
+                    format!("{:?}", ngram),
+                    fault_ratio(record) * 100.0,
+                    record.count_fault,
+                    record.count_correct + record.count_fault
+                );
+            }
+            report.push('\n');
+        }
+        // The keypad is only reported when it was used, most users never do
+        if !self.keypad.is_empty() {
+            let _ = writeln!(report, "Numeric keypad:");
+            for (key, record) in &self.keypad {
+                let _ = writeln!(
+                    report,
+                    "  {:<5} {:>6} ms  {:>5.1} %  ({} times)",
+                    format!("{:?}", key),
+                    record.speed.average_ms().unwrap_or(0),
+                    fault_ratio(record) * 100.0,
+                    record.count_correct + record.count_fault
+                );
+            }
+            report.push('\n');
+        }
+        report
+    }
+}
+
+/// Returns the ratio of mistakes to the total amount of keystrokes of a record.
+fn fault_ratio(record: &KeyRecord) -> f64 {
+    match record.count_correct + record.count_fault {
+        0 => 0.0,
+        total => record.count_fault as f64 / total as f64,
+    }
+}
+
+/// Create a drill that trains the given n-grams.
+///
+/// Every prompt of the drill trains a single n-gram with the words of the text that contain it.
+/// When the text does not contain the n-gram, the n-gram itself is repeated.
+pub fn drill(text: &[String], ngrams: &[String]) -> Vec<String> {
+    const WORDS: usize = 8;
+
+    let words: Vec<&str> = text.iter().flat_map(|s| s.split_whitespace()).collect();
+
+    ngrams
+        .iter()
+        .map(|ngram| {
+            let mut drill: Vec<&str> = Vec::new();
+            for word in words.iter().filter(|word| word.contains(ngram.as_str())) {
+                if !drill.contains(word) {
+                    drill.push(word);
+                }
+                if drill.len() == WORDS {
+                    break;
+                }
+            }
+            match drill.is_empty() {
+                true => [ngram.as_str(); WORDS].join(" "),
+                false => drill.join(" "),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,789 @@
+use crate::{
+    app::keys::{Interval, KeyRecord},
+    record::{self, KeyCounts, KeyStatsRecord, PromptCounts, Record, SettingsRecord},
+    stats::{self, Lock, NGrams},
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom, Write},
+    ops::Bound,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The version of the files of the store, a store of another version is rebuilt from the stats file.
+const STORE_VERSION: u64 = 2;
+
+/// The amount of bytes at the start of the stats file that is compared to notice that the file was rewritten.
+const PREFIX: u64 = 4096;
+
+/// The files of the store: the index, and the tables with a line for every session.
+const INDEX: &str = "index.json";
+const SESSIONS: &str = "sessions.jsonl";
+const KEYS: &str = "keys.jsonl";
+const PROMPTS: &str = "prompts.jsonl";
+
+/// The summary of the record of a single exercise.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Session {
+    pub id: usize, // the position of the session in the store, in the order the exercises were saved
+    #[serde(with = "record::rfc3339")]
+    pub date: DateTime<FixedOffset>,
+    pub path: String, // the full path of the file, or the name of a built-in or generated exercise
+    pub settings: String, // the settings that change the results, see [settings_key]
+    pub duration_ms: u64,
+    pub wpm: usize,
+    pub correct: usize,
+    pub mistakes: usize,
+    pub lenient: usize, // the correct characters that are only correct because of a leniency
+    pub valid: bool,    // false when text was pasted in the exercise
+}
+
+impl Session {
+    /// The name of the file of the exercise.
+    pub fn file(&self) -> &str {
+        Path::new(&self.path)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .unwrap_or(&self.path)
+    }
+}
+
+/// The conditions of a query on the store, a condition that is not given matches every session.
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    pub file: Option<String>,    // the full path or the name of the file
+    pub from: Option<NaiveDate>, // the first day, inclusive
+    pub to: Option<NaiveDate>,   // the last day, inclusive
+    pub settings: Vec<String>,   // the words the settings must contain, like "stop=error"
+}
+
+impl Query {
+    /// Returns true if the query matches every session.
+    pub fn is_empty(&self) -> bool {
+        self.file.is_none() && self.from.is_none() && self.to.is_none() && self.settings.is_empty()
+    }
+}
+
+/// The exact sums of the counters of an n-gram, the averages of the records can not be added without a loss.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Total {
+    correct: usize,
+    fault: usize,
+    timed: usize,
+    total_ms: u64,
+}
+
+impl Total {
+    /// Add the counters of a record.
+    fn add(&mut self, counts: &KeyCounts) {
+        self.correct += counts.count_correct;
+        self.fault += counts.count_fault;
+        self.timed += counts.count_timed;
+        self.total_ms += counts.average_ms.unwrap_or(0) * counts.count_timed as u64;
+    }
+
+    /// Returns the sums as a [KeyRecord].
+    fn key_record(&self) -> KeyRecord {
+        KeyRecord {
+            count_correct: self.correct,
+            count_fault: self.fault,
+            speed: Interval {
+                count: self.timed,
+                total: Duration::from_millis(self.total_ms),
+            },
+        }
+    }
+}
+
+/// The n-grams of all the valid sessions combined, they are kept up to date while the records are imported.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Totals {
+    bigrams: BTreeMap<String, Total>,
+    trigrams: BTreeMap<String, Total>,
+    keypad: BTreeMap<String, Total>,
+}
+
+impl Totals {
+    /// Add the n-grams of a session.
+    fn add(&mut self, keys: &KeyStatsRecord) {
+        for (totals, counts) in [
+            (&mut self.bigrams, &keys.bigrams),
+            (&mut self.trigrams, &keys.trigrams),
+            (&mut self.keypad, &keys.keypad),
+        ] {
+            for (ngram, counts) in counts {
+                totals.entry(ngram.clone()).or_default().add(counts);
+            }
+        }
+    }
+
+    /// Returns the combined n-grams.
+    fn ngrams(&self) -> NGrams {
+        let records = |totals: &BTreeMap<String, Total>| {
+            totals
+                .iter()
+                .map(|(ngram, total)| (ngram.clone(), total.key_record()))
+                .collect()
+        };
+        NGrams {
+            bigrams: records(&self.bigrams),
+            trigrams: records(&self.trigrams),
+            keypad: records(&self.keypad),
+        }
+    }
+}
+
+/// The index of the store, it is read completely when the store is opened.
+///
+/// The lines of the tables are found by their offset, so only the lines of a query are read.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+struct Index {
+    version: u64,
+    imported: u64,      // the amount of bytes of the stats file that are imported
+    prefix: u64,        // a hash of the start of the stats file, to notice a rewrite
+    sessions: Vec<u64>, // the offsets of the lines of the tables, by id
+    keys: Vec<u64>,
+    prompts: Vec<u64>,
+    by_path: BTreeMap<String, Vec<usize>>, // the ids of the sessions of every file
+    by_day: BTreeMap<String, Vec<usize>>,  // the ids of the sessions of every day, like 2024-01-31
+    by_settings: BTreeMap<String, Vec<usize>>, // the ids of the sessions with the same settings
+    invalid: BTreeSet<usize>, // the ids of the sessions with pasted text, they are not in the totals
+    totals: Totals,
+}
+
+/// A table of the store that new lines are appended to.
+///
+/// The lines are collected first and written at once, the offset of a line is known when it is added.
+struct Table {
+    path: PathBuf,
+    length: u64, // the length of the file before the new lines
+    lines: String,
+}
+
+impl Table {
+    /// Open the table to append lines to it.
+    fn open(directory: &Path, name: &str) -> Table {
+        let path = directory.join(name);
+        let length = fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        Table {
+            path,
+            length,
+            lines: String::new(),
+        }
+    }
+
+    /// Add a line to the table, returns its offset.
+    fn push(&mut self, line: &str) -> u64 {
+        let offset = self.length + self.lines.len() as u64;
+        self.lines.push_str(line);
+        self.lines.push('\n');
+        offset
+    }
+
+    /// Write the new lines to the file.
+    fn write(&self) -> Result<()> {
+        if self.lines.is_empty() {
+            return Ok(());
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(self.lines.as_bytes())?;
+        file.sync_data()?;
+        Ok(())
+    }
+}
+
+/// An indexed store of the history of the exercises, with the sessions, the results of their prompts
+/// and the statistics of their keys.
+///
+/// The store is kept in a directory next to the stats file. The stats file stays the log that every exercise
+/// is appended to, and the records that were added are imported when the store is opened.
+/// So an analysis only parses the new records, and a query only reads the sessions that match it.
+/// When the stats file was rewritten, by a migration or by hand, the store is rebuilt from it.
+pub struct Store {
+    directory: PathBuf,
+    index: Index,
+}
+
+impl Store {
+    /// Open the store of the stats file, and import the records that were added to the stats file since.
+    ///
+    /// Without a location for the stats file, there is no history and the store is empty.
+    pub fn open() -> Result<Store> {
+        match stats::path() {
+            Ok(stats) => Store::open_at(&stats),
+            Err(_) => Ok(Store {
+                directory: PathBuf::new(),
+                index: Index::default(),
+            }),
+        }
+    }
+
+    /// Open the store of the given stats file.
+    fn open_at(stats: &Path) -> Result<Store> {
+        let directory = stats::sibling(stats, ".store");
+        fs::create_dir_all(&directory)
+            .map_err(|e| anyhow!("Could not create {}: {}", directory.display(), e))?;
+
+        // Another run could be importing the same records
+        let _lock = Lock::acquire(&directory.join(INDEX))?;
+        let index = fs::read_to_string(directory.join(INDEX))
+            .ok()
+            .and_then(|index| serde_json::from_str(&index).ok())
+            .unwrap_or_default();
+        let mut store = Store { directory, index };
+        store.import(stats)?;
+        Ok(store)
+    }
+
+    /// Import the records that were added to the stats file since the last import.
+    ///
+    /// Only the complete lines are imported, a record that is still being written is imported the next time.
+    /// Returns the amount of imported sessions.
+    fn import(&mut self, stats: &Path) -> Result<usize> {
+        let mut file = match File::open(stats) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if !self.index.sessions.is_empty() {
+                    self.clear()?;
+                }
+                return Ok(0);
+            }
+            Err(e) => return Err(anyhow!("Could not read {}: {}", stats.display(), e)),
+        };
+        let length = file.metadata()?.len();
+        if self.index.version != STORE_VERSION
+            || length < self.index.imported
+            || prefix(&mut file, self.index.imported)? != self.index.prefix
+        {
+            self.clear()?;
+        }
+
+        let mut bytes = Vec::new();
+        file.seek(SeekFrom::Start(self.index.imported))?;
+        file.read_to_end(&mut bytes)?;
+        let Some(end) = bytes.iter().rposition(|&byte| byte == b'\n') else {
+            return Ok(0);
+        };
+
+        let mut tables = [SESSIONS, KEYS, PROMPTS].map(|name| Table::open(&self.directory, name));
+        let mut imported = 0;
+        for line in bytes[..end].split(|&byte| byte == b'\n') {
+            let Some(record) = std::str::from_utf8(line)
+                .ok()
+                .and_then(|line| Record::parse(line).ok())
+            else {
+                continue;
+            };
+            self.insert(record, &mut tables)?;
+            imported += 1;
+        }
+        // The tables are written before the index, the lines of an index that was not written are not used
+        for table in &tables {
+            table.write()?;
+        }
+        self.index.imported += end as u64 + 1;
+        self.index.prefix = prefix(&mut file, self.index.imported)?;
+        self.write_index()?;
+        Ok(imported)
+    }
+
+    /// Add the record of an exercise to the tables and the index.
+    fn insert(&mut self, record: Record, tables: &mut [Table; 3]) -> Result<()> {
+        let id = self.index.sessions.len();
+        let session = Session {
+            id,
+            date: record.date,
+            path: record
+                .content
+                .path
+                .or(record.content.file_path)
+                .unwrap_or_default(),
+            settings: settings_key(&record.settings),
+            duration_ms: record.timer.total_duration_ms,
+            wpm: record.wpm,
+            correct: record.stats.count_correct,
+            mistakes: record.stats.count_fault,
+            lenient: record.stats.count_lenient,
+            valid: record.valid,
+        };
+
+        let [sessions, keys, prompts] = tables;
+        let index = &mut self.index;
+        index
+            .sessions
+            .push(sessions.push(&serde_json::to_string(&session)?));
+        index
+            .keys
+            .push(keys.push(&serde_json::to_string(&record.stats.keys)?));
+        index
+            .prompts
+            .push(prompts.push(&serde_json::to_string(&record.prompts)?));
+        index.by_path.entry(session.path).or_default().push(id);
+        index
+            .by_day
+            .entry(session.date.format("%Y-%m-%d").to_string())
+            .or_default()
+            .push(id);
+        index
+            .by_settings
+            .entry(session.settings)
+            .or_default()
+            .push(id);
+        // The timings of pasted text would make the n-grams look faster than they are typed
+        match session.valid {
+            true => index.totals.add(&record.stats.keys),
+            false => {
+                index.invalid.insert(id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove all the sessions, to import the stats file again from the start.
+    fn clear(&mut self) -> Result<()> {
+        for name in [SESSIONS, KEYS, PROMPTS] {
+            match fs::remove_file(self.directory.join(name)) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        self.index = Index {
+            version: STORE_VERSION,
+            ..Index::default()
+        };
+        Ok(())
+    }
+
+    /// Write the index to a temporary file first, so the index is never broken.
+    fn write_index(&self) -> Result<()> {
+        let path = self.directory.join(INDEX);
+        let temporary = stats::sibling(&path, ".tmp");
+        fs::write(&temporary, serde_json::to_string(&self.index)?)?;
+        fs::rename(&temporary, &path)?;
+        Ok(())
+    }
+
+    /// Returns the ids of the sessions that match the query, in the order they were saved.
+    pub fn ids(&self, query: &Query) -> Vec<usize> {
+        let index = &self.index;
+        let mut ids: BTreeSet<usize> = (0..index.sessions.len()).collect();
+        let mut keep = |matching: BTreeSet<usize>| ids.retain(|id| matching.contains(id));
+
+        if let Some(file) = &query.file {
+            keep(union(index.by_path.iter().filter(|(path, _)| {
+                *path == file || Path::new(path).file_name() == Some(OsStr::new(file))
+            })));
+        }
+        if query.from.is_some() || query.to.is_some() {
+            let day = |date: NaiveDate| date.format("%Y-%m-%d").to_string();
+            let from = query
+                .from
+                .map_or(Bound::Unbounded, |from| Bound::Included(day(from)));
+            let to = query
+                .to
+                .map_or(Bound::Unbounded, |to| Bound::Included(day(to)));
+            keep(union(index.by_day.range((from, to))));
+        }
+        if !query.settings.is_empty() {
+            keep(union(index.by_settings.iter().filter(|(settings, _)| {
+                let words: Vec<&str> = settings.split(' ').collect();
+                query
+                    .settings
+                    .iter()
+                    .all(|word| words.contains(&word.as_str()))
+            })));
+        }
+        ids.into_iter().collect()
+    }
+
+    /// Returns the sessions that match the query, in the order they were saved.
+    pub fn sessions(&self, query: &Query) -> Result<Vec<Session>> {
+        let offsets = self
+            .ids(query)
+            .into_iter()
+            .map(|id| self.index.sessions[id]);
+        self.read(SESSIONS, offsets)
+    }
+
+    /// Returns the combined n-grams of the valid sessions that match the query.
+    pub fn ngrams(&self, query: &Query) -> Result<NGrams> {
+        if query.is_empty() {
+            return Ok(self.index.totals.ngrams());
+        }
+        let offsets = self
+            .ids(query)
+            .into_iter()
+            .filter(|id| !self.index.invalid.contains(id))
+            .map(|id| self.index.keys[id]);
+        let mut totals = Totals::default();
+        for keys in self.read::<KeyStatsRecord>(KEYS, offsets)? {
+            totals.add(&keys);
+        }
+        Ok(totals.ngrams())
+    }
+
+    /// Returns the results of the prompts of the sessions that match the query, in the same order as [Store::sessions].
+    ///
+    /// The sessions that were saved before the results of the prompts were recorded have no prompts.
+    pub fn prompts(&self, query: &Query) -> Result<Vec<Vec<PromptCounts>>> {
+        let offsets = self.ids(query).into_iter().map(|id| self.index.prompts[id]);
+        self.read(PROMPTS, offsets)
+    }
+
+    /// Returns the files of the previous exercises that still exist, the most recent file first.
+    pub fn recent_files(&self) -> Vec<PathBuf> {
+        const LIMIT: usize = 20;
+
+        let mut paths: Vec<(usize, PathBuf)> = self
+            .index
+            .by_path
+            .iter()
+            .filter_map(|(path, ids)| Some((*ids.last()?, PathBuf::from(path))))
+            // The built-in exercises have a name instead of a full path
+            .filter(|(_, path)| path.is_absolute() && path.is_file())
+            .collect();
+        paths.sort_by(|(a, _), (b, _)| b.cmp(a));
+        paths
+            .into_iter()
+            .take(LIMIT)
+            .map(|(_, path)| path)
+            .collect()
+    }
+
+    /// Read the lines of a table at the given offsets.
+    fn read<T: DeserializeOwned>(
+        &self,
+        table: &str,
+        offsets: impl Iterator<Item = u64>,
+    ) -> Result<Vec<T>> {
+        // The table of a store without sessions does not exist
+        let mut offsets = offsets.peekable();
+        if offsets.peek().is_none() {
+            return Ok(Vec::new());
+        }
+        let path = self.directory.join(table);
+        let mut reader = BufReader::new(
+            File::open(&path).map_err(|e| anyhow!("Could not read {}: {}", path.display(), e))?,
+        );
+        let mut position = 0;
+        let mut line = String::new();
+        let mut values = Vec::new();
+        for offset in offsets {
+            // The lines of most queries follow each other, the buffer is only dropped for a real jump
+            if offset != position {
+                reader.seek(SeekFrom::Start(offset))?;
+            }
+            line.clear();
+            position = offset + reader.read_line(&mut line)? as u64;
+            values.push(
+                serde_json::from_str(&line)
+                    .map_err(|e| anyhow!("The store at {} is broken: {}", path.display(), e))?,
+            );
+        }
+        Ok(values)
+    }
+}
+
+/// Returns the ids of all the given lists of an index.
+fn union<'a>(lists: impl Iterator<Item = (&'a String, &'a Vec<usize>)>) -> BTreeSet<usize> {
+    lists.flat_map(|(_, ids)| ids.iter().copied()).collect()
+}
+
+/// Returns the key of the settings that change the results of an exercise, like "qwerty backspace stop=error".
+///
+/// The settings that only change the interface, like an inline prompt, are left out.
+fn settings_key(settings: &SettingsRecord) -> String {
+    let leniency = &settings.leniency;
+    let mut words = vec![settings.layout.clone().unwrap_or(String::from("qwerty"))];
+    for (name, enabled) in [
+        ("blind", settings.blind),
+        ("backspace", settings.backspace),
+        ("word-backspace", settings.word_backspace),
+        ("highlight", settings.highlight),
+        ("auto", settings.auto),
+        ("correct", settings.correct),
+        ("repeat", settings.repeat),
+        ("ignore-case", leniency.case),
+        ("ignore-punctuation", leniency.punctuation),
+        ("ascii-quotes", leniency.typography),
+        ("fold-accents", leniency.accents),
+    ] {
+        if enabled {
+            words.push(name.to_string());
+        }
+    }
+    if let Some(stop) = &settings.stop {
+        words.push(format!("stop={}", stop));
+    }
+    words.join(" ")
+}
+
+/// Returns a hash of the first bytes of the file, at most [PREFIX] and at most the given length.
+///
+/// The bytes before the length do not change when records are appended, only when the file is rewritten.
+fn prefix(file: &mut File, length: u64) -> Result<u64> {
+    let mut bytes = Vec::new();
+    file.seek(SeekFrom::Start(0))?;
+    file.take(u64::min(length, PREFIX))
+        .read_to_end(&mut bytes)?;
+
+    // FNV-1a, the hash has to be the same in every version of the application
+    Ok(bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    /// Returns the path of a stats file in a new, empty directory.
+    fn stats_file(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "type_trainer_store_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory.join("stats.json")
+    }
+
+    /// Returns the line of a record with a single bigram "ab" that was typed correctly the given times.
+    fn line(day: &str, path: &str, settings: Value, correct: usize) -> String {
+        let record = json!({
+            "version": record::VERSION,
+            "date": format!("{}T12:00:00+01:00", day),
+            "content": { "path": path },
+            "settings": settings,
+            "stats": { "keys": { "bigrams": { "ab": { "count_correct": correct } } } },
+            "valid": true,
+        });
+        format!("{}\n", record)
+    }
+
+    /// Returns the line of a record with a field replaced.
+    fn with(line: &str, field: &str, value: Value) -> String {
+        let mut record: Value = serde_json::from_str(line).unwrap();
+        record[field] = value;
+        format!("{}\n", record)
+    }
+
+    fn append(stats: &Path, text: &str) {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(stats)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    fn paths(store: &Store) -> Vec<String> {
+        store
+            .sessions(&Query::default())
+            .unwrap()
+            .into_iter()
+            .map(|session| session.path)
+            .collect()
+    }
+
+    fn correct(store: &Store) -> usize {
+        let ngrams = store.ngrams(&Query::default()).unwrap();
+        ngrams.bigrams.get("ab").map_or(0, |ab| ab.count_correct)
+    }
+
+    #[test]
+    fn missing_stats_file_is_an_empty_history() {
+        let store = Store::open_at(&stats_file("missing")).unwrap();
+        assert!(store.sessions(&Query::default()).unwrap().is_empty());
+        assert!(store.ngrams(&Query::default()).unwrap().bigrams.is_empty());
+        assert!(store.recent_files().is_empty());
+    }
+
+    #[test]
+    fn only_new_records_are_imported() {
+        let stats = stats_file("incremental");
+        append(&stats, &line("2024-01-30", "a.txt", json!({}), 1));
+        append(&stats, &line("2024-01-31", "b.txt", json!({}), 2));
+        let store = Store::open_at(&stats).unwrap();
+        assert_eq!(paths(&store), ["a.txt", "b.txt"]);
+        assert_eq!(correct(&store), 3);
+
+        // A record that is still being written is imported once it is complete
+        let partial = line("2024-02-01", "d.txt", json!({}), 8);
+        let (start, end) = partial.split_at(20);
+        append(&stats, &line("2024-02-01", "c.txt", json!({}), 4));
+        append(&stats, start);
+        let store = Store::open_at(&stats).unwrap();
+        assert_eq!(paths(&store), ["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(correct(&store), 7);
+        assert!(store.index.imported < fs::metadata(&stats).unwrap().len());
+
+        append(&stats, end);
+        let store = Store::open_at(&stats).unwrap();
+        assert_eq!(paths(&store), ["a.txt", "b.txt", "c.txt", "d.txt"]);
+        assert_eq!(correct(&store), 15);
+        assert_eq!(store.index.imported, fs::metadata(&stats).unwrap().len());
+    }
+
+    #[test]
+    fn rewritten_stats_file_is_imported_again() {
+        let stats = stats_file("rewrite");
+        let lines = [
+            line("2024-01-30", "a.txt", json!({}), 1),
+            line("2024-01-31", "b.txt", json!({}), 2),
+            line("2024-02-01", "c.txt", json!({}), 4),
+        ];
+        fs::write(&stats, lines.concat()).unwrap();
+        Store::open_at(&stats).unwrap();
+
+        // The file is longer than before, only the start of the file shows that it was rewritten
+        let first = line("2024-01-30", "x.txt", json!({}), 16);
+        let more = line("2024-02-02", "d.txt", json!({}), 32);
+        let rewritten = [&first, &lines[1], &lines[2], &more].map(String::as_str);
+        fs::write(&stats, rewritten.concat()).unwrap();
+        let store = Store::open_at(&stats).unwrap();
+        assert_eq!(paths(&store), ["x.txt", "b.txt", "c.txt", "d.txt"]);
+        assert_eq!(correct(&store), 54);
+
+        // A file that is shorter than the imported part was rewritten as well
+        fs::write(&stats, &lines[0]).unwrap();
+        let store = Store::open_at(&stats).unwrap();
+        assert_eq!(paths(&store), ["a.txt"]);
+        assert_eq!(correct(&store), 1);
+    }
+
+    #[test]
+    fn sessions_of_a_query() {
+        let stats = stats_file("query");
+        let backspace = json!({ "backspace": true });
+        let lines = [
+            line(
+                "2024-01-30",
+                "/texts/a.txt",
+                json!({ "backspace": true, "stop": "error" }),
+                1,
+            ),
+            line("2024-01-31", "/texts/b.txt", json!({}), 2),
+            line("2024-02-01", "/other/a.txt", backspace, 4),
+            line("2024-02-02", "numbers", json!({ "stop": "error" }), 8),
+        ];
+        fs::write(&stats, lines.concat()).unwrap();
+        let store = Store::open_at(&stats).unwrap();
+        let day = |day: &str| NaiveDate::parse_from_str(day, "%Y-%m-%d").ok();
+        let by_file = |file: &str| {
+            store.ids(&Query {
+                file: Some(String::from(file)),
+                ..Query::default()
+            })
+        };
+        let by_days = |from: &str, to: &str| {
+            store.ids(&Query {
+                from: day(from),
+                to: day(to),
+                ..Query::default()
+            })
+        };
+        let by_settings = |words: &[&str]| {
+            store.ids(&Query {
+                settings: words.iter().map(|word| word.to_string()).collect(),
+                ..Query::default()
+            })
+        };
+
+        // A file is found by its full path or by its name
+        assert_eq!(by_file("a.txt"), [0, 2]);
+        assert_eq!(by_file("/texts/a.txt"), [0]);
+        assert_eq!(by_file("numbers"), [3]);
+        assert!(by_file("c.txt").is_empty());
+
+        // Both days of the range are included, a missing day leaves the range open
+        assert_eq!(by_days("2024-01-31", "2024-02-01"), [1, 2]);
+        assert_eq!(by_days("2024-02-01", ""), [2, 3]);
+        assert_eq!(by_days("", "2024-01-30"), [0]);
+
+        // Every word has to be part of the settings
+        assert_eq!(by_settings(&["backspace"]), [0, 2]);
+        assert_eq!(by_settings(&["backspace", "stop=error"]), [0]);
+        assert_eq!(by_settings(&["qwerty"]), [0, 1, 2, 3]);
+        assert!(by_settings(&["stop"]).is_empty());
+
+        // The conditions are combined
+        let query = Query {
+            file: Some(String::from("a.txt")),
+            from: day("2024-01-31"),
+            ..Query::default()
+        };
+        assert_eq!(store.ids(&query), [2]);
+        let ngrams = store.ngrams(&query).unwrap();
+        assert_eq!(ngrams.bigrams["ab"].count_correct, 4);
+    }
+
+    #[test]
+    fn prompts_of_a_query() {
+        let stats = stats_file("prompts");
+        let prompts = json!([
+            { "chars": 5, "correct": 5, "time_ms": 1000 },
+            { "chars": 3, "correct": 2, "mistakes": 1, "time_ms": 800 },
+        ]);
+        let first = line("2024-01-30", "a.txt", json!({}), 1);
+        let lines = [
+            with(&first, "prompts", prompts),
+            line("2024-01-31", "b.txt", json!({}), 2),
+        ];
+        fs::write(&stats, lines.concat()).unwrap();
+        let store = Store::open_at(&stats).unwrap();
+
+        let prompts = store.prompts(&Query::default()).unwrap();
+        assert_eq!(prompts.len(), 2);
+        let counts: Vec<(usize, usize)> = prompts[0]
+            .iter()
+            .map(|prompt| (prompt.chars, prompt.mistakes))
+            .collect();
+        assert_eq!(counts, [(5, 0), (3, 1)]);
+        // A record without the results of its prompts
+        assert!(prompts[1].is_empty());
+
+        let query = Query {
+            file: Some(String::from("b.txt")),
+            ..Query::default()
+        };
+        let prompts = store.prompts(&query).unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].is_empty());
+    }
+
+    #[test]
+    fn pasted_sessions_are_left_out_of_the_ngrams() {
+        let stats = stats_file("pasted");
+        let pasted = line("2024-01-31", "a.txt", json!({}), 2);
+        let lines = [
+            line("2024-01-30", "a.txt", json!({}), 1),
+            with(&pasted, "valid", json!(false)),
+        ];
+        fs::write(&stats, lines.concat()).unwrap();
+        let store = Store::open_at(&stats).unwrap();
+        assert_eq!(paths(&store), ["a.txt", "a.txt"]);
+        assert_eq!(correct(&store), 1);
+
+        let query = Query {
+            file: Some(String::from("a.txt")),
+            ..Query::default()
+        };
+        let ngrams = store.ngrams(&query).unwrap();
+        assert_eq!(ngrams.bigrams["ab"].count_correct, 1);
+    }
+}
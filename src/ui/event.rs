@@ -1,15 +1,15 @@
 use anyhow::Result;
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, MouseEvent};
 use std::{
-    sync::mpsc,
+    sync::mpsc::{self, RecvTimeoutError},
     thread,
-    time::{Duration, Instant},
+    time::Instant,
 };
 
 /// Terminal events.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Event {
-    /// Terminal tick.
+    /// The deadline that was given to [EventHandler::next] has passed.
     Tick,
     /// Key press.
     Key(KeyEvent),
@@ -17,6 +17,12 @@ pub enum Event {
     Mouse(MouseEvent),
     /// Terminal resize.
     Resize(u16, u16),
+    /// Text that was pasted in the terminal.
+    Paste(String),
+    /// The terminal gained focus.
+    FocusGained,
+    /// The terminal lost focus.
+    FocusLost,
 }
 
 /// Terminal event handler.
@@ -34,41 +40,30 @@ pub struct EventHandler {
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
-    pub fn new(tick_rate: u64) -> Self {
-        let tick_rate = Duration::from_millis(tick_rate);
+    ///
+    /// The handler thread blocks until the terminal has an event, so no CPU is used while the user is idle.
+    pub fn new() -> Self {
         let (sender, receiver) = mpsc::channel();
         let handler = {
             let sender = sender.clone();
 
-            // Spawning a new thread that polls for events and sends them to our main application
-            thread::spawn(move || {
-                let mut last_tick = Instant::now();
-                loop {
-                    let timeout = tick_rate
-                        .checked_sub(last_tick.elapsed())
-                        .unwrap_or(tick_rate);
-
-                    if event::poll(timeout).expect("unable to poll for event") {
-                        match event::read().expect("unable to read event") {
-                            CrosstermEvent::Key(e) => {
-                                if e.kind == event::KeyEventKind::Press {
-                                    sender.send(Event::Key(e))
-                                } else {
-                                    Ok(()) // ignore KeyEventKind::Release on windows
-                                }
-                            }
-                            CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
-                            CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
-                            _ => unimplemented!(),
+            // Spawning a new thread that waits for events and sends them to our main application
+            thread::spawn(move || loop {
+                match event::read().expect("unable to read event") {
+                    CrosstermEvent::Key(e) => {
+                        if e.kind == event::KeyEventKind::Press {
+                            sender.send(Event::Key(e))
+                        } else {
+                            Ok(()) // ignore KeyEventKind::Release on windows
                         }
-                        .expect("failed to send terminal event")
-                    }
-
-                    if last_tick.elapsed() >= tick_rate {
-                        sender.send(Event::Tick).expect("failed to send tick event");
-                        last_tick = Instant::now();
                     }
+                    CrosstermEvent::Mouse(e) => sender.send(Event::Mouse(e)),
+                    CrosstermEvent::Resize(w, h) => sender.send(Event::Resize(w, h)),
+                    CrosstermEvent::Paste(text) => sender.send(Event::Paste(text)),
+                    CrosstermEvent::FocusGained => sender.send(Event::FocusGained),
+                    CrosstermEvent::FocusLost => sender.send(Event::FocusLost),
                 }
+                .expect("failed to send terminal event")
             })
         };
         Self {
@@ -80,9 +75,27 @@ impl EventHandler {
 
     /// Receive the next event from the handler thread.
     ///
-    /// This function will always block the current thread if
-    /// there is no data available and it's possible for more data to be sent.
-    pub fn next(&self) -> Result<Event> {
-        Ok(self.receiver.recv()?)
+    /// This function will block the current thread until there is an event,
+    /// or until the optional deadline has passed, in which case [Event::Tick] is returned.
+    pub fn next(&self, deadline: Option<Instant>) -> Result<Event> {
+        match deadline {
+            Some(deadline) => {
+                match self
+                    .receiver
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                {
+                    Ok(event) => Ok(event),
+                    Err(RecvTimeoutError::Timeout) => Ok(Event::Tick),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            None => Ok(self.receiver.recv()?),
+        }
+    }
+}
+
+impl Default for EventHandler {
+    fn default() -> Self {
+        Self::new()
     }
 }
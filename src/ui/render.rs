@@ -1,30 +1,59 @@
-use crate::app::{
-    content::Content, exercise::Exercise, prompt::Prompt, state::State, timer::Timer,
+use crate::{
+    app::{
+        bindings::Action,
+        builtin::BUILTINS,
+        content::Content,
+        exercise::Exercise,
+        generator::GENERATORS,
+        keys::KeyRecord,
+        menu::{Menu, Tab},
+        prompt::{graphemes, is_whitespace, Prompt},
+        settings::Setting,
+        state::State,
+        timer::Timer,
+    },
+    ui::theme::Theme,
 };
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     prelude::{Alignment, Frame},
-    style::{Color, Modifier, Style, Stylize},
+    style::{Modifier, Style, Stylize},
     symbols,
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, LineGauge, Padding, Paragraph, Wrap},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Cell, Clear, LineGauge, List, ListItem, ListState, Padding,
+        Paragraph, Row, Table, Tabs, Wrap,
+    },
 };
+use unicode_normalization::UnicodeNormalization;
+use unicode_width::UnicodeWidthStr;
 
-/// Renders the Ratatui widgets on the screen based on the state of the [Exercise]
-pub fn render(exercise: &Exercise, f: &mut Frame) {
+/// The character that is used for the cursor and for the extra characters
+const BLOCK: &str = symbols::block::FULL;
+
+/// Renders the Ratatui widgets on the screen based on the state of the [Exercise] with the colours of the [Theme]
+pub fn render(exercise: &Exercise, theme: &Theme, f: &mut Frame) {
     let border = Block::default()
         .title(" Type Trainer ")
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
-        .border_type(BorderType::Double);
+        .border_type(BorderType::Double)
+        .style(theme.border);
 
     let inner = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(vec![
-            Constraint::Length(11), // information block
-            Constraint::Min(7),     // prompt area
-            Constraint::Length(7),  // type area
-        ])
+        .constraints(match exercise.settings.inline {
+            false => vec![
+                Constraint::Length(11), // information block
+                Constraint::Min(7),     // prompt area
+                Constraint::Length(7),  // type area
+            ],
+            true => vec![
+                Constraint::Length(11), // information block
+                Constraint::Min(7),     // prompt area, the text is typed over the prompt
+            ],
+        })
         .vertical_margin(2)
         .horizontal_margin(1)
         .split(border.inner(f.size()));
@@ -52,42 +81,253 @@ pub fn render(exercise: &Exercise, f: &mut Frame) {
         .split(border.inner(top[1]));
 
     f.render_widget(border, f.size());
-    f.render_widget(timer(&exercise.timer), top_left[0]);
-    f.render_widget(progress_bar(&exercise.content), top_left[1]);
-    f.render_widget(ratio_bar(&exercise.prompt), top_left[2]);
-    f.render_widget(info(&exercise), top_right[0]);
-    f.render_widget(wpm(&exercise), top_right[1]);
-    f.render_widget(prompt(exercise), inner[1]);
-    f.render_widget(typed(exercise), inner[2]);
+    f.render_widget(timer(&exercise.timer, theme), top_left[0]);
+    f.render_widget(progress_bar(exercise, theme), top_left[1]);
+    f.render_widget(ratio_bar(exercise, theme), top_left[2]);
+    f.render_widget(info(exercise, theme), top_right[0]);
+    f.render_widget(wpm(exercise, theme), top_right[1]);
+    match exercise.state {
+        State::Finished => match &exercise.save_error {
+            Some(error) => {
+                let area = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(vec![Constraint::Length(4), Constraint::Min(0)])
+                    .split(inner[1]);
+                f.render_widget(save_error(error, theme), area[0]);
+                f.render_widget(fingers(exercise, theme), area[1]);
+            }
+            None => f.render_widget(fingers(exercise, theme), inner[1]),
+        },
+        _ if exercise.settings.inline => {
+            f.render_widget(prompt_inline(exercise, theme, inner[1]), inner[1])
+        }
+        _ => f.render_widget(prompt(exercise, theme, inner[1]), inner[1]),
+    }
+    if !exercise.settings.inline {
+        f.render_widget(typed(exercise, theme, inner[2]), inner[2]);
+    }
+    if let Some(selected) = exercise.overlay {
+        let area = centre(inner[1], 34, Setting::ALL.len() as u16 + 4);
+        let mut state = ListState::default().with_selected(Some(selected));
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(settings_overlay(exercise, theme), area, &mut state);
+    }
+}
+
+/// Returns an area with the given size in the centre of the given area
+fn centre(area: Rect, width: u16, height: u16) -> Rect {
+    let width = u16::min(width, area.width);
+    let height = u16::min(height, area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Create the overlay with the settings that are used when the exercise is restarted
+///
+/// A setting that is different from the settings of the current exercise is marked.
+fn settings_overlay<'a>(exercise: &Exercise, theme: &Theme) -> List<'a> {
+    let items: Vec<ListItem> = Setting::ALL
+        .iter()
+        .map(|&setting| {
+            let label = exercise.next_settings.label(setting);
+            let changed = match label == exercise.settings.label(setting) {
+                true => ' ',
+                false => '*',
+            };
+            ListItem::new(format!("{:<12}{:>8} {}", setting, label, changed))
+        })
+        .collect();
+
+    List::new(items)
+        .style(theme.text)
+        .highlight_style(theme.prompt.add_modifier(Modifier::REVERSED))
+        .block(
+            Block::default()
+                .title("Settings")
+                .title(Title::from("Applied on restart").position(Position::Bottom))
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(theme.prompt)
+                .padding(Padding::horizontal(1)),
+        )
+}
+
+/// Renders the [Menu] that is shown when no file is given, with the colours of the [Theme]
+pub fn render_menu(menu: &Menu, theme: &Theme, f: &mut Frame) {
+    let border = Block::default()
+        .title(" Type Trainer ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .style(theme.border);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(3), // tabs
+            Constraint::Min(5),    // items of the selected tab
+            Constraint::Length(5), // help
+        ])
+        .vertical_margin(2)
+        .horizontal_margin(1)
+        .split(border.inner(f.size()));
+
+    let mut state = ListState::default().with_selected(Some(menu.cursor()));
+
+    f.render_widget(border, f.size());
+    f.render_widget(menu_tabs(menu, theme), inner[0]);
+    f.render_stateful_widget(menu_items(menu, theme), inner[1], &mut state);
+    f.render_widget(menu_help(menu, theme), inner[2]);
+}
+
+/// Create the widget with the tabs of the [Menu]
+fn menu_tabs<'a>(menu: &Menu, theme: &Theme) -> Tabs<'a> {
+    Tabs::new(Tab::ALL.iter().map(|tab| tab.to_string()).collect())
+        .select(menu.tab.index())
+        .style(theme.text)
+        .highlight_style(theme.prompt.add_modifier(Modifier::BOLD))
+        .block(Block::default().borders(Borders::ALL))
+}
+
+/// Create the widget with the items of the selected tab of the [Menu]
+fn menu_items<'a>(menu: &Menu, theme: &Theme) -> List<'a> {
+    let (title, items): (String, Vec<String>) = match menu.tab {
+        Tab::Recent => (
+            String::from("Recent files"),
+            menu.recent
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect(),
+        ),
+        Tab::Files => (
+            menu.directory.display().to_string(),
+            menu.entries
+                .iter()
+                .map(|entry| match entry.directory {
+                    true => format!("{}/", entry.name),
+                    false => entry.name.clone(),
+                })
+                .collect(),
+        ),
+        Tab::Exercises => (
+            String::from("Built-in exercises"),
+            BUILTINS
+                .iter()
+                .map(|builtin| (builtin.name, builtin.description))
+                .chain(
+                    GENERATORS
+                        .iter()
+                        .map(|generator| (generator.name, generator.description)),
+                )
+                .map(|(name, description)| format!("{:<14} {}", name, description))
+                .collect(),
+        ),
+        Tab::Settings => (
+            String::from("Settings"),
+            menu.fields
+                .iter()
+                .map(|field| format!("--{:<10} {:>7}", field.long, field.label()))
+                .collect(),
+        ),
+    };
+
+    List::new(
+        items
+            .into_iter()
+            .map(ListItem::new)
+            .collect::<Vec<ListItem>>(),
+    )
+    .style(theme.text)
+    .highlight_style(theme.prompt.add_modifier(Modifier::REVERSED))
+    .block(
+        Block::default()
+            .title(title)
+            .title_alignment(Alignment::Left)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.prompt)
+            .padding(Padding::horizontal(1)),
+    )
+}
+
+/// Create the widget with the error of the [Menu], or the description of the selected item,
+/// together with the keys that can be used in the selected tab
+fn menu_help<'a>(menu: &Menu, theme: &Theme) -> Paragraph<'a> {
+    let description = match (&menu.message, menu.tab) {
+        (Some(message), _) => Line::styled(message.clone(), theme.extra),
+        (None, Tab::Recent) if menu.recent.is_empty() => {
+            Line::from("The files of your exercises are shown here")
+        }
+        (None, Tab::Settings) => Line::from(
+            menu.fields
+                .get(menu.cursor())
+                .map(|field| field.help.clone())
+                .unwrap_or_default(),
+        ),
+        (None, _) => Line::from(""),
+    };
+    let keys = match menu.tab {
+        Tab::Recent | Tab::Exercises => "Start: 'Enter'   Switch: 'Tab'   Quit: 'Esc'",
+        Tab::Files => "Open: 'Enter'   Up: 'Backspace'   Switch: 'Tab'   Quit: 'Esc'",
+        Tab::Settings => "Change: 'Left'/'Right'/'Space'   Switch: 'Tab'   Quit: 'Esc'",
+    };
+
+    Paragraph::new(vec![description, Line::from(""), Line::from(keys)])
+        .style(theme.text)
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .title("Help")
+                .title_alignment(Alignment::Left)
+                .borders(Borders::ALL),
+        )
 }
 
 /// Create the widget with the state of the application and the buttons to change the state
-fn info(exercise: &Exercise) -> Paragraph {
-    let options: Vec<&str> = match exercise.state {
-        State::Waiting => vec![State::Running.button(), "", "", State::Quitting.button()],
+fn info<'a>(exercise: &'a Exercise, theme: &Theme) -> Paragraph<'a> {
+    let start = String::from("Start:   Type");
+    let button = |action: Action| {
+        format!(
+            "{:<9}'{}'",
+            format!("{}:", action),
+            exercise.bindings.get(action)
+        )
+    };
+    let options: Vec<String> = match exercise.state {
+        State::Waiting => vec![
+            start,
+            String::new(),
+            button(Action::Settings),
+            button(Action::Quit),
+        ],
         State::Running => vec![
-            State::Pausing.button(),
-            "",
-            State::Waiting.button(),
-            State::Finished.button(),
+            button(Action::Pause),
+            String::new(),
+            button(Action::Restart),
+            button(Action::Stop),
         ],
         State::Pausing => vec![
-            State::Running.button(),
-            "",
-            State::Waiting.button(),
-            State::Finished.button(),
+            start,
+            String::new(),
+            button(Action::Restart),
+            button(Action::Stop),
+        ],
+        State::Finished => vec![
+            button(Action::Settings),
+            String::new(),
+            button(Action::Restart),
+            button(Action::Quit),
         ],
-        State::Finished => vec!["", "", State::Waiting.button(), State::Quitting.button()],
         State::Quitting => vec![],
     };
 
-    let text = Text::from(
-        options
-            .iter()
-            .map(|&option| Line::from(option))
-            .collect::<Vec<Line>>(),
-    );
-    Paragraph::new(text).block(
+    let text = Text::from(options.into_iter().map(Line::from).collect::<Vec<Line>>());
+    Paragraph::new(text).style(theme.text).block(
         Block::default()
             .title(exercise.state.to_string())
             .title_alignment(Alignment::Left)
@@ -95,12 +335,21 @@ fn info(exercise: &Exercise) -> Paragraph {
     )
 }
 
-/// Create the widget that displays the current wpm
-fn wpm(exercise: &Exercise) -> Paragraph {
-    Paragraph::new(format!("{} WPM", exercise.calculate_wpm()))
+/// Create the widget that displays the current wpm, of the last minutes for an endless exercise
+///
+/// The title warns that the score is invalid when text was pasted.
+fn wpm<'a>(exercise: &Exercise, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(format!("{} WPM", exercise.current_wpm()))
+        .style(theme.text)
         .block(
             Block::new()
-                .title("Words per minute")
+                .title(match (exercise.is_valid(), &exercise.rolling) {
+                    (true, None) => Span::from("Words per minute"),
+                    (true, Some(rolling)) => {
+                        Span::from(format!("WPM (last {} min)", rolling.window.as_secs() / 60))
+                    }
+                    (false, _) => Span::styled("Invalid: pasted", theme.extra),
+                })
                 .title_alignment(Alignment::Left)
                 .borders(Borders::ALL),
         )
@@ -108,129 +357,406 @@ fn wpm(exercise: &Exercise) -> Paragraph {
 }
 
 /// Create the timer widget that shows the time since the start of the [Exercise]
-fn timer(timer: &Timer) -> LineGauge {
+fn timer<'a>(timer: &Timer, theme: &Theme) -> LineGauge<'a> {
     LineGauge::default()
         .block(Block::default().borders(Borders::ALL).title("Timer"))
-        .gauge_style(Style::default().fg(Color::White).bg(Color::Black))
+        .gauge_style(theme.timer)
         .ratio(timer.ratio())
         .line_set(linegauge_set_dots())
         .label(timer.get_time_label())
 }
 
 /// Create the progress widget that shows the percentage of prompts in the [Content] that are finished
-fn progress_bar(content: &Content) -> LineGauge {
-    LineGauge::default()
+///
+/// When a target is set, it shows the typed amount of words or characters compared to the target instead.
+fn progress_bar<'a>(exercise: &Exercise, theme: &Theme) -> LineGauge<'a> {
+    let gauge = LineGauge::default()
         .block(Block::default().borders(Borders::ALL).title("Progress"))
-        .gauge_style(Style::default().fg(Color::White).bg(Color::Black))
-        .ratio(content.ratio())
-        .line_set(symbols::line::THICK)
+        .gauge_style(theme.progress)
+        .line_set(symbols::line::THICK);
+
+    match (exercise.target_progress(), exercise.settings.target) {
+        (Some((done, amount)), Some(target)) => gauge
+            .ratio(f64::min(done as f64 / amount as f64, 1.0))
+            .label(format!("{}/{}", done, target)),
+        _ => gauge.ratio(exercise.content.ratio()),
+    }
 }
 
 /// Create the widget that shows the ratio of correctly typed characters to the total amount of typed characters
-fn ratio_bar(prompt: &Prompt) -> LineGauge {
+///
+/// For an endless exercise, only the characters of the last minutes are counted.
+fn ratio_bar<'a>(exercise: &Exercise, theme: &Theme) -> LineGauge<'a> {
     LineGauge::default()
-        .block(Block::default().borders(Borders::ALL).title("Ratio"))
-        .gauge_style(Style::default().fg(Color::LightGreen).bg(Color::LightRed))
-        .ratio(prompt.ratio())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(match &exercise.rolling {
+                    Some(rolling) => format!("Ratio (last {} min)", rolling.window.as_secs() / 60),
+                    None => String::from("Ratio"),
+                }),
+        )
+        .gauge_style(theme.ratio)
+        .ratio(exercise.current_ratio())
         .line_set(symbols::line::THICK)
 }
 
-const ORANGE: Color = Color::Rgb(255, 140, 0);
-
 /// Create the widget that shows the current prompt and the following prompts
-fn prompt(exercise: &Exercise) -> Paragraph {
-    let text = match exercise.state {
+fn prompt<'a>(exercise: &Exercise, theme: &Theme, area: Rect) -> Paragraph<'a> {
+    let block = prompt_block(theme);
+    let (lines, offset) = match exercise.state {
         State::Waiting | State::Running | State::Pausing => {
-            let mut styled = match exercise.settings.highlight {
-                true => get_prompt_highlight(&exercise.prompt),
-                false => get_prompt(&exercise.prompt),
+            let styled = match exercise.settings.highlight {
+                true => get_prompt_highlight(&exercise.prompt, theme),
+                false => get_prompt(&exercise.prompt, theme),
             };
-            // Append the following lines without extra styling
-            styled.extend(exercise.content.get_next_prompts());
-            styled
+            // The cursor is on the next character of the prompt, the extra characters come after it
+            let cursor = usize::min(exercise.prompt.typed.len(), exercise.prompt.prompt.len());
+            viewport(&exercise.content, styled, cursor, theme, block.inner(area), false)
         }
-        _ => Text::from(""),
+        _ => (Vec::new(), 0),
     };
 
-    Paragraph::new(text).wrap(Wrap { trim: false }).block(
-        Block::default()
-            .title("Prompt")
-            .title_alignment(Alignment::Left)
-            .borders(Borders::ALL)
-            .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(ORANGE))
-            .padding(Padding::uniform(1)),
-    )
+    Paragraph::new(lines)
+        .scroll((offset, 0))
+        .block(block)
+}
+
+/// Create the block around the prompt
+fn prompt_block<'a>(theme: &Theme) -> Block<'a> {
+    Block::default()
+        .title("Prompt")
+        .title_alignment(Alignment::Left)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(theme.prompt)
+        .padding(Padding::uniform(1))
+}
+
+/// Create the widget that shows the current prompt with the typed text on top of it, followed by the next prompts
+///
+/// The characters that are not typed yet are dimmed, and the cursor moves through the prompt.
+/// When highlighting is enabled, the correct characters keep the pending style
+/// and the mistakes show the wrongly typed character in the extra style.
+/// In blind mode, only the position of the cursor shows what was typed,
+/// the characters typed past the end of the prompt are shown as blocks like in the prompt box.
+///
+/// The widget scrolls to keep the line of the cursor in the centre.
+fn prompt_inline<'a>(exercise: &Exercise, theme: &Theme, area: Rect) -> Paragraph<'a> {
+    let block = prompt_block(theme);
+
+    let prompt = &exercise.prompt.prompt;
+    let typed = &exercise.prompt.typed;
+    let dimmed = theme.pending.add_modifier(Modifier::DIM);
+
+    let mut styled: Vec<(String, Style)> = Vec::new();
+    for i in 0..usize::max(prompt.len(), typed.len()) {
+        styled.push(match (prompt.get(i), typed.get(i)) {
+            (Some(p), None) => (p.clone(), dimmed),
+            (Some(p), Some(_)) if exercise.settings.blind => (p.clone(), theme.pending),
+            (Some(p), Some(_)) if exercise.prompt.correct_at(i) || !exercise.settings.highlight => {
+                (p.clone(), theme.pending)
+            }
+            // A wrongly typed space would be invisible
+            (Some(p), Some(t)) if is_whitespace(t) => (p.clone(), theme.incorrect),
+            (Some(_), Some(t)) => (t.clone(), theme.extra),
+            (None, Some(_)) if exercise.settings.blind => (String::from(BLOCK), theme.extra),
+            (None, Some(t)) => (t.clone(), theme.extra),
+            (None, None) => unreachable!(),
+        });
+    }
+    // The cursor is placed on the next character, or after the end of the prompt
+    let cursor = typed.len();
+    match styled.get_mut(cursor) {
+        Some((_, style)) => *style = style.patch(theme.cursor).add_modifier(Modifier::UNDERLINED),
+        None => styled.push((String::from(" "), theme.cursor.add_modifier(Modifier::REVERSED))),
+    }
+    // The characters that are being composed are shown underlined at the cursor, like an input method does
+    let composing = &exercise.prompt.composing;
+    if !composing.is_empty() && !exercise.settings.blind {
+        let (g, style) = &mut styled[cursor];
+        *g = composing.nfc().collect();
+        *style = style.patch(theme.typed).add_modifier(Modifier::UNDERLINED);
+    }
+    // The character that was refused by the stop mode is shown at the cursor
+    match &exercise.prompt.rejected {
+        Some(r) if exercise.settings.highlight && !exercise.settings.blind => {
+            let (g, style) = &mut styled[cursor];
+            if !is_whitespace(r) {
+                *g = r.clone();
+            }
+            *style = style.patch(theme.incorrect);
+        }
+        _ => {}
+    }
+
+    let (lines, offset) = viewport(&exercise.content, styled, cursor, theme, block.inner(area), true);
+    Paragraph::new(lines)
+        .scroll((offset, 0))
+        .block(block)
+}
+
+/// Create the lines of the visible part of the prompts.
+///
+/// The styled characters of the current prompt are followed by the next prompts,
+/// only the prompts that fit in the area are requested from the [Content].
+/// The lines are wrapped at the width of the area instead of at the words, so the line of the cursor is known.
+///
+/// Returns the lines together with the amount of lines that have to be scrolled
+/// to keep the cursor visible, or to keep it in the centre of the area.
+fn viewport<'a>(
+    content: &Content,
+    styled: Vec<(String, Style)>,
+    cursor: usize,
+    theme: &Theme,
+    area: Rect,
+    centre: bool,
+) -> (Vec<Line<'a>>, u16) {
+    let width = usize::max(area.width as usize, 1);
+    let height = area.height as usize;
+
+    let (mut lines, cursor_line) = wrap(&styled, width, cursor);
+    let offset = match centre {
+        true => cursor_line.saturating_sub(height / 2),
+        false => cursor_line.saturating_sub(height.saturating_sub(1)),
+    };
+
+    // Every following prompt takes at least one line
+    let remaining = (offset + height).saturating_sub(lines.len());
+    for next in content.get_next_prompts(remaining) {
+        let next: Vec<(String, Style)> = graphemes(next)
+            .into_iter()
+            .map(|g| (g, theme.upcoming))
+            .collect();
+        lines.extend(wrap(&next, width, 0).0);
+    }
+
+    (lines, offset as u16)
+}
+
+/// Split the styled graphemes into lines with a fixed width.
+///
+/// The width of every grapheme is the amount of columns it takes in the terminal,
+/// so a wide character moves to the next line when only one column is left.
+/// The consecutive graphemes with the same style are combined in a single [Span].
+///
+/// Returns the lines together with the line of the grapheme at the cursor.
+fn wrap<'a>(styled: &[(String, Style)], width: usize, cursor: usize) -> (Vec<Line<'a>>, usize) {
+    let mut lines: Vec<Vec<&(String, Style)>> = vec![Vec::new()];
+    let mut cursor_line = 0;
+    let mut used = 0;
+    for (i, grapheme) in styled.iter().enumerate() {
+        let columns = usize::max(grapheme.0.width(), 1);
+        if used > 0 && used + columns > width {
+            lines.push(Vec::new());
+            used = 0;
+        }
+        if i == cursor {
+            cursor_line = lines.len() - 1;
+        }
+        lines.last_mut().expect("There is always a line").push(grapheme);
+        used += columns;
+    }
+
+    let lines = lines
+        .into_iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let mut spans: Vec<Span> = Vec::new();
+            let mut text = String::new();
+            for (i, (g, style)) in line.iter().enumerate() {
+                text.push_str(g);
+                if line.get(i + 1).map_or(true, |(_, next)| next != style) {
+                    spans.push(Span::styled(std::mem::take(&mut text), *style));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect();
+    (lines, cursor_line)
 }
 
-/// Use the [Prompt] to create styled text without highlighting
-fn get_prompt(prompt: &Prompt) -> Text {
-    Text::from(Line::from(
-        Span::from(prompt.prompt.iter().collect::<String>()).fg(ORANGE),
-    ))
+/// Use the [Prompt] to create styled characters without highlighting
+fn get_prompt(prompt: &Prompt, theme: &Theme) -> Vec<(String, Style)> {
+    prompt
+        .prompt
+        .iter()
+        .map(|g| (g.clone(), theme.pending))
+        .collect()
 }
 
-/// Use the [Prompt] to create styled text with highlighting
+/// Use the [Prompt] to create styled characters with highlighting
 ///
-/// The text of the current prompt is orange
-/// Highlight the prompt in green if the typed text is correct
-/// Highlight the prompt in red if the typed text is wrong
-fn get_prompt_highlight(prompt: &Prompt) -> Text {
+/// The text of the current prompt has the pending style of the [Theme]
+/// Highlight the prompt with the correct style if the typed text is correct
+/// Highlight the prompt with the incorrect style if the typed text is wrong
+fn get_prompt_highlight(prompt: &Prompt, theme: &Theme) -> Vec<(String, Style)> {
+    let rejected = &prompt.rejected;
+    let correct = |i| prompt.correct_at(i);
     let typed = &prompt.typed;
     let prompt = &prompt.prompt;
 
-    let mut prompt_styled: Vec<Span> = Vec::new();
+    let mut prompt_styled: Vec<(String, Style)> = Vec::new();
 
     // check for each letter if it is correct and style accordingly
-    for i in 0..usize::min(typed.len(), prompt.len()) {
-        prompt_styled.push(
-            Span::from(prompt[i].to_string())
-                .bg(match prompt[i] == typed[i] {
-                    true => Color::Green,
-                    false => Color::Red,
-                })
-                .fg(ORANGE),
-        )
+    for (i, grapheme) in prompt.iter().enumerate().take(typed.len()) {
+        prompt_styled.push((
+            grapheme.clone(),
+            match correct(i) {
+                true => theme.correct,
+                false => theme.incorrect,
+            },
+        ))
     }
-    // if typed is longer than prompt, we add red spaces for each unnecessary letter
+    // if typed is longer than prompt, we add blocks for each unnecessary letter
     if typed.len() > prompt.len() {
-        prompt_styled.push(
-            Span::from(
-                std::iter::repeat(symbols::block::FULL)
-                    .take(typed.len() - prompt.len())
-                    .collect::<String>(),
-            )
-            .fg(Color::Red),
+        prompt_styled.extend(
+            std::iter::repeat((String::from(BLOCK), theme.extra)).take(typed.len() - prompt.len()),
         )
     }
-    // The rest of the line should not be styled
+    // The rest of the line is still pending
     else if typed.len() < prompt.len() {
-        prompt_styled.push(Span::from(prompt[typed.len()..].iter().collect::<String>()).fg(ORANGE));
+        prompt_styled.extend(prompt[typed.len()..].iter().map(|g| (g.clone(), theme.pending)));
+    }
+    // The character at the cursor was refused by the stop mode
+    if let (Some(_), Some((_, style))) = (rejected, prompt_styled.get_mut(typed.len())) {
+        *style = theme.incorrect;
     }
 
-    Text::from(Line::from(prompt_styled))
+    prompt_styled
 }
 
-/// Create a widget that shows the text typed by the user for the current prompt
-fn typed(exercise: &Exercise) -> Paragraph {
-    let typed = match exercise.state {
-        State::Waiting | State::Running | State::Pausing if !exercise.settings.blind => {
-            let mut spans = vec![Span::from(exercise.prompt.typed.iter().collect::<String>())];
-            // Add a cursor to the typed text
-            spans.push(Span::from(symbols::block::FULL).add_modifier(Modifier::SLOW_BLINK));
-            Text::from(Line::from(spans))
-        }
-        _ => Text::from(""),
+/// Create the widget that shows the mistakes and the speed of each finger when the exercise is finished
+///
+/// The finger with the highest ratio of mistakes is highlighted with the incorrect style.
+/// The last rows compare the speed of the transitions between the keys.
+fn fingers<'a>(exercise: &Exercise, theme: &Theme) -> Table<'a> {
+    let keys = &exercise.prompt.keys;
+    let weakest = keys.weakest_finger().map(|(finger, _)| finger);
+
+    let row = |name: String, record: &KeyRecord| {
+        Row::new(vec![
+            Cell::from(name),
+            Cell::from(record.count_correct.to_string()),
+            Cell::from(record.count_fault.to_string()),
+            Cell::from(milliseconds(record.speed.average_ms())),
+        ])
     };
 
-    Paragraph::new(typed)
+    let mut rows: Vec<Row> = keys
+        .fingers()
+        .iter()
+        .map(|(finger, record)| match Some(*finger) == weakest {
+            true => row(finger.to_string(), record).style(theme.incorrect),
+            false => row(finger.to_string(), record),
+        })
+        .collect();
+    rows.extend(
+        keys.hands()
+            .iter()
+            .map(|(hand, record)| row(format!("{} hand", hand), record).bold()),
+    );
+    let keypad = keys.keypad_total();
+    if keypad.count_correct + keypad.count_fault > 0 {
+        rows.push(row(String::from("Keypad"), &keypad).bold());
+    }
+    for (name, interval) in [
+        ("Same finger", &keys.same_finger),
+        ("Same hand", &keys.same_hand),
+        ("Alternation", &keys.alternation),
+    ] {
+        rows.push(Row::new(vec![
+            Cell::from(name),
+            Cell::from(interval.count.to_string()),
+            Cell::from(""),
+            Cell::from(milliseconds(interval.average_ms())),
+        ]));
+    }
+
+    Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ],
+    )
+    .header(Row::new(vec!["Finger", "Correct", "Mistakes", "Speed"]).underlined())
+    .style(theme.text)
+    .block(
+        Block::default()
+            .title(format!("Fingers ({})", keys.layout.name))
+            .title_alignment(Alignment::Left)
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(theme.prompt)
+            .padding(Padding::uniform(1)),
+    )
+}
+
+/// Create the widget that warns that the results of the finished exercise could not be saved
+fn save_error<'a>(error: &str, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(error.to_string())
+        .style(theme.text)
+        .wrap(Wrap { trim: true })
         .block(
             Block::default()
-                .title("Typed:")
+                .title(Span::styled("The results are not saved", theme.incorrect))
+                .title_alignment(Alignment::Left)
                 .borders(Borders::ALL)
-                .padding(Padding::uniform(1)),
+                .border_type(BorderType::Rounded)
+                .border_style(theme.incorrect),
         )
-        .wrap(Wrap { trim: false })
+}
+
+/// Format an optional average interval
+fn milliseconds(ms: Option<u64>) -> String {
+    match ms {
+        Some(ms) => format!("{} ms", ms),
+        None => String::from("-"),
+    }
+}
+
+/// Create a widget that shows the text typed by the user for the current prompt
+///
+/// The text is wrapped at a fixed width and scrolls to keep the cursor visible.
+fn typed<'a>(exercise: &Exercise, theme: &Theme, area: Rect) -> Paragraph<'a> {
+    let block = Block::default()
+        .title("Typed:")
+        .borders(Borders::ALL)
+        .padding(Padding::uniform(1));
+    let inner = block.inner(area);
+
+    let (lines, offset) = match exercise.state {
+        State::Waiting | State::Running | State::Pausing if !exercise.settings.blind => {
+            let mut styled: Vec<(String, Style)> = exercise
+                .prompt
+                .typed
+                .iter()
+                .map(|g| (g.clone(), theme.typed))
+                .collect();
+            // Add a cursor to the typed text, a character that was refused by the stop mode is shown before it
+            if let Some(r) = &exercise.prompt.rejected {
+                styled.push((r.clone(), theme.extra));
+            }
+            if !exercise.prompt.composing.is_empty() {
+                let style = theme.typed.add_modifier(Modifier::UNDERLINED);
+                styled.push((exercise.prompt.composing.nfc().collect(), style));
+            }
+            styled.push((String::from(BLOCK), theme.typed.patch(theme.cursor)));
+
+            let (lines, _) = wrap(&styled, usize::max(inner.width as usize, 1), 0);
+            let offset = lines.len().saturating_sub(inner.height as usize);
+            (lines, offset as u16)
+        }
+        _ => (Vec::new(), 0),
+    };
+
+    Paragraph::new(lines)
+        .style(theme.typed)
+        .scroll((offset, 0))
+        .block(block)
 }
 
 /// Used to change the timer widget to show dots instead of a full line
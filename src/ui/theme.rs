@@ -0,0 +1,268 @@
+use anyhow::{anyhow, Result};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::{collections::BTreeMap, str::FromStr};
+
+const ORANGE: Color = Color::Rgb(255, 140, 0);
+
+/// The styles of all the widgets and the characters of the prompt.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub border: Style,    // the border around the application
+    pub text: Style,      // the text of the information, wpm and statistics widgets
+    pub timer: Style,     // the timer gauge, the background is used for the unfilled part
+    pub progress: Style,  // the progress gauge, the background is used for the unfilled part
+    pub ratio: Style,     // the ratio gauge, the background is used for the unfilled part
+    pub prompt: Style,    // the border of the prompt widget
+    pub upcoming: Style,  // the prompts that follow the current prompt
+    pub typed: Style,     // the text in the typed widget
+    pub cursor: Style,    // the cursor in the typed widget
+    pub correct: Style,   // the correctly typed characters of the prompt
+    pub incorrect: Style, // the wrongly typed characters of the prompt
+    pub pending: Style,   // the characters of the prompt that are not typed yet
+    pub extra: Style,     // the characters that are typed past the end of the prompt
+}
+
+impl Theme {
+    /// The names of the themes that are built in.
+    pub const NAMES: [&'static str; 4] = ["dark", "light", "high-contrast", "deuteranopia"];
+
+    /// The default theme for terminals with a dark background.
+    pub fn dark() -> Theme {
+        Theme {
+            name: String::from("dark"),
+            border: Style::new(),
+            text: Style::new(),
+            timer: Style::new().fg(Color::White).bg(Color::Black),
+            progress: Style::new().fg(Color::White).bg(Color::Black),
+            ratio: Style::new().fg(Color::LightGreen).bg(Color::LightRed),
+            prompt: Style::new().fg(ORANGE),
+            upcoming: Style::new(),
+            typed: Style::new(),
+            cursor: Style::new().add_modifier(Modifier::SLOW_BLINK),
+            correct: Style::new().fg(ORANGE).bg(Color::Green),
+            incorrect: Style::new().fg(ORANGE).bg(Color::Red),
+            pending: Style::new().fg(ORANGE),
+            extra: Style::new().fg(Color::Red),
+        }
+    }
+
+    /// A theme for terminals with a light background.
+    pub fn light() -> Theme {
+        let accent = Color::Rgb(175, 80, 0);
+        Theme {
+            name: String::from("light"),
+            border: Style::new().fg(Color::Black),
+            text: Style::new().fg(Color::Black),
+            timer: Style::new().fg(Color::Black).bg(Color::Gray),
+            progress: Style::new().fg(Color::Black).bg(Color::Gray),
+            ratio: Style::new().fg(Color::Green).bg(Color::Red),
+            prompt: Style::new().fg(accent),
+            upcoming: Style::new().fg(Color::DarkGray),
+            typed: Style::new().fg(Color::Black),
+            cursor: Style::new()
+                .fg(Color::Black)
+                .add_modifier(Modifier::SLOW_BLINK),
+            correct: Style::new().fg(Color::Black).bg(Color::LightGreen),
+            incorrect: Style::new().fg(Color::Black).bg(Color::LightRed),
+            pending: Style::new().fg(accent),
+            extra: Style::new().fg(Color::Red),
+        }
+    }
+
+    /// A theme with maximal contrast that does not only rely on colours to mark the mistakes.
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: String::from("high-contrast"),
+            border: Style::new().fg(Color::White).bg(Color::Black),
+            text: Style::new().fg(Color::White).bg(Color::Black),
+            timer: Style::new().fg(Color::White).bg(Color::Black),
+            progress: Style::new().fg(Color::White).bg(Color::Black),
+            ratio: Style::new().fg(Color::White).bg(Color::DarkGray),
+            prompt: Style::new()
+                .fg(Color::Yellow)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            upcoming: Style::new().fg(Color::White).bg(Color::Black),
+            typed: Style::new().fg(Color::White).bg(Color::Black),
+            cursor: Style::new()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::SLOW_BLINK),
+            correct: Style::new().fg(Color::Black).bg(Color::White),
+            incorrect: Style::new()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            pending: Style::new()
+                .fg(Color::Yellow)
+                .bg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+            extra: Style::new()
+                .fg(Color::Red)
+                .add_modifier(Modifier::UNDERLINED),
+        }
+    }
+
+    /// A theme that replaces red and green with the blue and orange of the Okabe-Ito palette,
+    /// these colours can be distinguished with deuteranopia.
+    pub fn deuteranopia() -> Theme {
+        let blue = Color::Rgb(0, 114, 178);
+        let vermillion = Color::Rgb(213, 94, 0);
+        let yellow = Color::Rgb(240, 228, 66);
+        Theme {
+            name: String::from("deuteranopia"),
+            border: Style::new(),
+            text: Style::new(),
+            timer: Style::new().fg(Color::White).bg(Color::Black),
+            progress: Style::new().fg(Color::White).bg(Color::Black),
+            ratio: Style::new().fg(blue).bg(vermillion),
+            prompt: Style::new().fg(yellow),
+            upcoming: Style::new(),
+            typed: Style::new(),
+            cursor: Style::new().add_modifier(Modifier::SLOW_BLINK),
+            correct: Style::new().fg(Color::White).bg(blue),
+            incorrect: Style::new()
+                .fg(Color::White)
+                .bg(vermillion)
+                .add_modifier(Modifier::UNDERLINED),
+            pending: Style::new().fg(yellow),
+            extra: Style::new().fg(vermillion),
+        }
+    }
+
+    /// Find a theme by its name.
+    ///
+    /// The custom themes of the configuration file are searched first,
+    /// so they can also replace a theme that is built in.
+    pub fn from_name(name: &str, custom: &BTreeMap<String, ThemeConfig>) -> Result<Theme> {
+        Theme::resolve(name, custom, &mut Vec::new())
+    }
+
+    /// Find a theme by its name, the visited themes are used to detect a cycle of base themes.
+    ///
+    /// A custom theme that replaces a theme that is built in can use the original theme as its base.
+    fn resolve<'a>(
+        name: &'a str,
+        custom: &'a BTreeMap<String, ThemeConfig>,
+        visited: &mut Vec<&'a str>,
+    ) -> Result<Theme> {
+        match custom.get(name) {
+            Some(config) if !visited.contains(&name) => {
+                visited.push(name);
+
+                let base = config.base.as_deref().unwrap_or("dark");
+                let mut theme = Theme::resolve(base, custom, visited)?;
+                theme.name = name.to_string();
+                for (field, style) in &config.styles {
+                    theme.apply(field, style)?;
+                }
+                return Ok(theme);
+            }
+            Some(_) if !Theme::NAMES.contains(&name) => {
+                return Err(anyhow!("The theme '{}' is based on itself", name));
+            }
+            _ => {}
+        }
+
+        match name {
+            "dark" => Ok(Theme::dark()),
+            "light" => Ok(Theme::light()),
+            "high-contrast" => Ok(Theme::high_contrast()),
+            "deuteranopia" => Ok(Theme::deuteranopia()),
+            _ => Err(anyhow!(
+                "Unknown theme '{}', the available themes are: {}",
+                name,
+                Theme::NAMES
+                    .iter()
+                    .copied()
+                    .chain(
+                        custom
+                            .keys()
+                            .map(|k| k.as_str())
+                            .filter(|k| !Theme::NAMES.contains(k))
+                    )
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            )),
+        }
+    }
+
+    /// Change the style of a single widget or character state with the style from the configuration file.
+    fn apply(&mut self, field: &str, config: &StyleConfig) -> Result<()> {
+        let style = match field {
+            "border" => &mut self.border,
+            "text" => &mut self.text,
+            "timer" => &mut self.timer,
+            "progress" => &mut self.progress,
+            "ratio" => &mut self.ratio,
+            "prompt" => &mut self.prompt,
+            "upcoming" => &mut self.upcoming,
+            "typed" => &mut self.typed,
+            "cursor" => &mut self.cursor,
+            "correct" => &mut self.correct,
+            "incorrect" => &mut self.incorrect,
+            "pending" => &mut self.pending,
+            "extra" => &mut self.extra,
+            _ => {
+                return Err(anyhow!(
+                    "Unknown style '{}' in theme '{}'",
+                    field,
+                    self.name
+                ))
+            }
+        };
+        *style = style.patch(config.to_style()?);
+        Ok(())
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+/// A custom theme in the configuration file.
+///
+/// Only the styles that are different from the base theme have to be specified.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ThemeConfig {
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub styles: BTreeMap<String, StyleConfig>,
+}
+
+/// A style in the configuration file.
+///
+/// The colours can be a name like "red" or "light-blue", an index like "208", or a hex code like "#ff8c00".
+/// The modifiers are names like "bold", "italic", "underlined" or "reversed".
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct StyleConfig {
+    pub fg: Option<String>,
+    pub bg: Option<String>,
+    pub modifiers: Vec<String>,
+}
+
+impl StyleConfig {
+    /// Convert the configured style to a [Style]
+    fn to_style(&self) -> Result<Style> {
+        let color = |c: &str| Color::from_str(c).map_err(|_| anyhow!("Invalid colour '{}'", c));
+
+        let mut style = Style::new();
+        if let Some(fg) = &self.fg {
+            style = style.fg(color(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(color(bg)?);
+        }
+        for modifier in &self.modifiers {
+            style = style.add_modifier(
+                Modifier::from_name(&modifier.to_uppercase())
+                    .ok_or_else(|| anyhow!("Invalid modifier '{}'", modifier))?,
+            );
+        }
+        Ok(style)
+    }
+}
@@ -1,11 +1,18 @@
 use anyhow::Result;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{
+        DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use std::{io, panic};
 pub type CrosstermTerminal = ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stderr>>;
-use crate::{app::exercise::Exercise, ui::event::EventHandler, ui::render};
+use crate::{
+    app::{exercise::Exercise, menu::Menu},
+    ui::{event::EventHandler, render, theme::Theme},
+};
 
 /// Representation of a terminal user interface.
 ///
@@ -16,20 +23,39 @@ pub struct Tui {
     terminal: CrosstermTerminal,
     /// Terminal event handler.
     pub events: EventHandler,
+    /// The colours of the widgets.
+    pub theme: Theme,
 }
 
 impl Tui {
     /// Constructs a new instance of [Tui].
-    pub fn new(terminal: CrosstermTerminal, events: EventHandler) -> Self {
-        Self { terminal, events }
+    pub fn new(terminal: CrosstermTerminal, events: EventHandler, theme: Theme) -> Self {
+        Self {
+            terminal,
+            events,
+            theme,
+        }
     }
 
     /// Initializes the terminal interface.
     ///
     /// It enables the raw mode and sets terminal properties.
+    /// Pasted text is received as a single event instead of separate key presses.
+    /// The keys of the numeric keypad are told apart from the other keys in the terminals that support it.
     pub fn enter(&mut self) -> Result<()> {
         terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stderr(), EnterAlternateScreen, EnableMouseCapture)?;
+        crossterm::execute!(
+            io::stderr(),
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableBracketedPaste,
+            EnableFocusChange
+        )?;
+        // The other terminals ignore the flags, only the legacy Windows console returns an error
+        let _ = crossterm::execute!(
+            io::stderr(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        );
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
@@ -49,7 +75,17 @@ impl Tui {
     /// [Draw]: crate::ui::tui::Tui::draw
     /// [rendering]: crate::ui::render::render
     pub fn draw(&mut self, exercise: &mut Exercise) -> Result<()> {
-        self.terminal.draw(|frame| render::render(exercise, frame))?;
+        self.terminal
+            .draw(|frame| render::render(exercise, &self.theme, frame))?;
+        Ok(())
+    }
+
+    /// [Draw] the [Menu] that is shown when no file is given.
+    ///
+    /// [Draw]: crate::ui::tui::Tui::draw
+    pub fn draw_menu(&mut self, menu: &Menu) -> Result<()> {
+        self.terminal
+            .draw(|frame| render::render_menu(menu, &self.theme, frame))?;
         Ok(())
     }
 
@@ -58,8 +94,15 @@ impl Tui {
     /// This function is also used for the panic hook to revert
     /// the terminal properties if unexpected errors occur.
     fn reset() -> Result<()> {
+        let _ = crossterm::execute!(io::stderr(), PopKeyboardEnhancementFlags);
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stderr(), LeaveAlternateScreen, DisableMouseCapture)?;
+        crossterm::execute!(
+            io::stderr(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            DisableBracketedPaste,
+            DisableFocusChange
+        )?;
         Ok(())
     }
 